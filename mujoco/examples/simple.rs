@@ -13,12 +13,12 @@ const MJCF: &str = r#"<mujoco>
 
 fn main() {
     let model = Model::from_xml_str(MJCF).unwrap();
-    let simulation = Simulation::new(model);
+    let mut simulation = Simulation::new(model);
 
     for _ in 0..1000 {
         println!(
             "time: {}, xpos: {:?}",
-            simulation.state.time(),
+            simulation.state().time(),
             simulation.xpos()
         );
         simulation.step();
@@ -0,0 +1,186 @@
+use std::os::raw::c_int;
+
+use mujoco_rs_sys::no_render::{mjModel, mjNBIAS, mjNDYN, mjNGAIN, mjtTrn};
+
+use crate::helpers::read_array;
+use crate::id::{ActuatorId, BodyId, JointId, SiteId, TendonId};
+use crate::re_exports::{BiasType, DynType, GainType};
+
+pub fn dyn_type_from(val: c_int) -> DynType {
+    [
+        DynType::NONE,
+        DynType::INTEGRATOR,
+        DynType::FILTER,
+        DynType::MUSCLE,
+        DynType::USER,
+    ]
+    .iter()
+    .copied()
+    .find(|&t| t as c_int == val)
+    .expect("Invalid value for DynType")
+}
+
+pub fn gain_type_from(val: c_int) -> GainType {
+    [
+        GainType::FIXED,
+        GainType::AFFINE,
+        GainType::MUSCLE,
+        GainType::USER,
+    ]
+    .iter()
+    .copied()
+    .find(|&t| t as c_int == val)
+    .expect("Invalid value for GainType")
+}
+
+pub fn bias_type_from(val: c_int) -> BiasType {
+    [
+        BiasType::NONE,
+        BiasType::AFFINE,
+        BiasType::MUSCLE,
+        BiasType::USER,
+    ]
+    .iter()
+    .copied()
+    .find(|&t| t as c_int == val)
+    .expect("Invalid value for BiasType")
+}
+
+fn trn_type_from(val: c_int) -> mjtTrn {
+    [
+        mjtTrn::JOINT,
+        mjtTrn::JOINTINPARENT,
+        mjtTrn::SLIDERCRANK,
+        mjtTrn::TENDON,
+        mjtTrn::SITE,
+        mjtTrn::BODY,
+        mjtTrn::UNDEFINED,
+    ]
+    .iter()
+    .copied()
+    .find(|&t| t as c_int == val)
+    .expect("Invalid value for TrnType")
+}
+
+/// What an actuator applies its force to, along with the ids of the objects
+/// involved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Transmission {
+    /// Force on a joint
+    Joint(JointId),
+    /// Force on a joint, with the gear expressed in the frame of the parent
+    /// body (only differs from [`Transmission::Joint`] for ball and free
+    /// joints)
+    JointInParent(JointId),
+    /// A slider-crank mechanism, where `slider` is the site of the slider and
+    /// `crank` is the site at the end of the crank
+    SliderCrank { crank: SiteId, slider: SiteId },
+    /// Force on a tendon
+    Tendon(TendonId),
+    /// Wrench at a site, optionally relative to a reference site
+    Site {
+        site: SiteId,
+        refsite: Option<SiteId>,
+    },
+    /// Adhesion force on the geoms of a body
+    Body(BodyId),
+    /// The actuator's transmission is not set, or refers to an object that
+    /// doesn't exist, so it has no effect
+    Undefined,
+}
+
+impl Transmission {
+    fn from_raw(trn_type: c_int, trn_id: [c_int; 2]) -> Self {
+        let id = match crate::id::index_from_raw(trn_id[0]) {
+            Some(id) => id,
+            None => return Self::Undefined,
+        };
+        let other = crate::id::index_from_raw(trn_id[1]);
+        match (trn_type_from(trn_type), other) {
+            (mjtTrn::JOINT, _) => Self::Joint(JointId::new(id)),
+            (mjtTrn::JOINTINPARENT, _) => Self::JointInParent(JointId::new(id)),
+            (mjtTrn::SLIDERCRANK, Some(slider)) => Self::SliderCrank {
+                crank: SiteId::new(id),
+                slider: SiteId::new(slider),
+            },
+            (mjtTrn::TENDON, _) => Self::Tendon(TendonId::new(id)),
+            (mjtTrn::SITE, _) => Self::Site {
+                site: SiteId::new(id),
+                refsite: other.map(SiteId::new),
+            },
+            (mjtTrn::BODY, _) => Self::Body(BodyId::new(id)),
+            (mjtTrn::SLIDERCRANK, None) | (mjtTrn::UNDEFINED, _) => Self::Undefined,
+        }
+    }
+}
+
+/// An actuator of a [`Model`](crate::Model)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Actuator {
+    pub id: ActuatorId,
+    pub name: String,
+    pub transmission: Transmission,
+    /// Scales the length and force of the transmission. Only the first entry
+    /// is used, except by joint and site transmissions in 3D.
+    pub gear: [f64; 6],
+    /// The range that controls are clamped to, if the actuator is limited
+    pub ctrl_range: Option<[f64; 2]>,
+    /// The range that the force is clamped to, if it is limited
+    pub force_range: Option<[f64; 2]>,
+    /// The range that the activation is clamped to, if it is limited
+    pub act_range: Option<[f64; 2]>,
+    /// Offset of the actuator's activations in [`Simulation::act()`], if it
+    /// has any
+    ///
+    /// [`Simulation::act()`]: crate::Simulation::act
+    pub act_addr: Option<usize>,
+    pub act_num: usize,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::DynTypeDef"))]
+    pub dyn_type: DynType,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::GainTypeDef"))]
+    pub gain_type: GainType,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::BiasTypeDef"))]
+    pub bias_type: BiasType,
+    pub dyn_prm: [f64; mjNDYN as usize],
+    pub gain_prm: [f64; mjNGAIN as usize],
+    pub bias_prm: [f64; mjNBIAS as usize],
+}
+
+impl Actuator {
+    /// Reads actuator `i` out of `m`
+    ///
+    /// # Safety
+    /// `i` must be less than `m.nu`.
+    pub(crate) unsafe fn from_model(m: &mjModel, i: usize, name: &str) -> Self {
+        let limited = |flags: *const u8| *flags.add(i) != 0;
+        let range = |flags: *const u8, range: *const f64| {
+            if limited(flags) {
+                Some(read_array(range, i))
+            } else {
+                None
+            }
+        };
+        Self {
+            id: ActuatorId::new(i),
+            name: name.to_owned(),
+            transmission: Transmission::from_raw(
+                *m.actuator_trntype.add(i),
+                read_array(m.actuator_trnid, i),
+            ),
+            gear: read_array(m.actuator_gear, i),
+            ctrl_range: range(m.actuator_ctrllimited, m.actuator_ctrlrange),
+            force_range: range(m.actuator_forcelimited, m.actuator_forcerange),
+            act_range: range(m.actuator_actlimited, m.actuator_actrange),
+            act_addr: crate::id::index_from_raw(*m.actuator_actadr.add(i)),
+            act_num: *m.actuator_actnum.add(i) as usize,
+            dyn_type: dyn_type_from(*m.actuator_dyntype.add(i)),
+            gain_type: gain_type_from(*m.actuator_gaintype.add(i)),
+            bias_type: bias_type_from(*m.actuator_biastype.add(i)),
+            dyn_prm: read_array(m.actuator_dynprm, i),
+            gain_prm: read_array(m.actuator_gainprm, i),
+            bias_prm: read_array(m.actuator_biasprm, i),
+        }
+    }
+}
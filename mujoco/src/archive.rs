@@ -0,0 +1,132 @@
+//! Reading the files out of zip and tar(.gz) asset bundles, entirely in memory
+
+use std::io::{Cursor, Read};
+
+use crate::Error;
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+
+/// A file read out of an archive, with its path inside the archive
+pub(crate) struct Entry {
+    pub path: String,
+    pub contents: Vec<u8>,
+}
+
+/// Reads every file in a zip, tar, or gzipped tar archive. The format is
+/// detected from the contents, and directories are skipped.
+pub(crate) fn read_archive(bytes: &[u8]) -> Result<Vec<Entry>, Error> {
+    if bytes.starts_with(ZIP_MAGIC) {
+        read_zip(bytes)
+    } else if bytes.starts_with(GZIP_MAGIC) {
+        read_tar(flate2::read::GzDecoder::new(bytes))
+    } else {
+        read_tar(bytes)
+    }
+}
+
+/// Strips the leading `./` that tar commonly adds to paths
+fn normalize_path(path: &str) -> String {
+    path.trim_start_matches("./").to_owned()
+}
+
+fn archive_err(e: impl std::fmt::Display) -> Error {
+    Error::InvalidArchive(e.to_string())
+}
+
+fn read_zip(bytes: &[u8]) -> Result<Vec<Entry>, Error> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(bytes)).map_err(archive_err)?;
+    let mut entries = Vec::with_capacity(zip.len());
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i).map_err(archive_err)?;
+        if file.is_dir() {
+            continue;
+        }
+        // The size in the header is not trusted for preallocating, as a
+        // malformed archive could claim any size
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents).map_err(archive_err)?;
+        entries.push(Entry {
+            path: normalize_path(file.name()),
+            contents,
+        });
+    }
+    Ok(entries)
+}
+
+fn read_tar(reader: impl Read) -> Result<Vec<Entry>, Error> {
+    let mut tar = tar::Archive::new(reader);
+    let mut entries = Vec::new();
+    for file in tar.entries().map_err(archive_err)? {
+        let mut file = file.map_err(archive_err)?;
+        if !file.header().entry_type().is_file() {
+            continue;
+        }
+        let path = file.path().map_err(archive_err)?;
+        let path = path
+            .to_str()
+            .ok_or_else(|| Error::NonUtf8Path(path.to_path_buf()))?;
+        let path = normalize_path(path);
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents).map_err(archive_err)?;
+        entries.push(Entry { path, contents });
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io::Write;
+
+    use super::read_archive;
+
+    /// Builds a zip archive in memory from `(path, contents)` pairs
+    pub(crate) fn make_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (path, contents) in files {
+            writer
+                .start_file(*path, zip::write::FileOptions::default())
+                .unwrap();
+            writer.write_all(contents).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    /// Builds a gzipped tar archive in memory from `(path, contents)` pairs
+    pub(crate) fn make_tar_gz(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let encoder =
+            flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (path, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *contents).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn zip() {
+        let bytes = make_zip(&[("a/b.txt", b"asdf")]);
+        let entries = read_archive(&bytes).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, "a/b.txt");
+        assert_eq!(entries[0].contents, b"asdf");
+    }
+
+    #[test]
+    fn tar_gz() {
+        let bytes = make_tar_gz(&[("./a/b.txt", b"asdf")]);
+        let entries = read_archive(&bytes).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, "a/b.txt");
+        assert_eq!(entries[0].contents, b"asdf");
+    }
+
+    #[test]
+    fn garbage() {
+        assert!(read_archive(b"PK\x03\x04 not a zip").is_err());
+    }
+}
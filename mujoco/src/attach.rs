@@ -0,0 +1,482 @@
+//! Composing models by attaching one MJCF document as a subtree of another.
+//!
+//! Every name in the attached model, and every reference to one, is prefixed so
+//! that the names in the combined model stay unambiguous. Assets are matched by
+//! MuJoCo on filename alone, so models that come from different directories can
+//! be compiled together with [`Model::from_xml_str_with_assets()`].
+
+use std::collections::BTreeMap;
+
+use xmltree::{Element, XMLNode};
+
+use crate::{Error, Model};
+
+/// Where in the parent model the child model is attached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachPoint<'a> {
+    /// The child's world becomes part of the named body. `"world"` attaches the
+    /// child at the parent's world body.
+    Body(&'a str),
+    /// The child's world is placed in a new body at the pose of the named site
+    Site(&'a str),
+}
+
+/// Attributes that hold the name of another element
+const REFERENCE_ATTRS: &[&str] = &[
+    "class",
+    "childclass",
+    "material",
+    "mesh",
+    "hfield",
+    "texture",
+    "target",
+    "body",
+    "body1",
+    "body2",
+    "geom",
+    "geom1",
+    "geom2",
+    "joint",
+    "joint1",
+    "joint2",
+    "jointinparent",
+    "tendon",
+    "tendon1",
+    "tendon2",
+    "site",
+    "sidesite",
+    "refsite",
+    "cranksite",
+    "slidersite",
+    "actuator",
+    "objname",
+    "refname",
+];
+
+/// Attributes that place a site, and so the body that replaces it
+const POSE_ATTRS: &[&str] = &["pos", "quat", "axisangle", "xyaxes", "zaxis", "euler"];
+
+/// Sections whose children are merged into the parent's section of the same name
+const MERGED_SECTIONS: &[&str] = &[
+    "asset", "actuator", "sensor", "tendon", "equality", "contact", "custom",
+];
+
+/// Elements outside of the world body that accept a default class
+const CLASSED_ELEMENTS: &[&str] = &[
+    "mesh",
+    "material",
+    "spatial",
+    "fixed",
+    "connect",
+    "weld",
+    "joint",
+    "tendon",
+    "pair",
+    "general",
+    "motor",
+    "position",
+    "velocity",
+    "intvelocity",
+    "damper",
+    "cylinder",
+    "muscle",
+    "adhesion",
+];
+
+/// Attaches the MJCF in `child` to the MJCF in `parent`, at `at`, and returns
+/// the combined MJCF. Every name in `child` is prefixed with `prefix`.
+///
+/// The child's `<default>` becomes a default class named `{prefix}main`, which
+/// its elements use, but defaults set at the top level of the parent also apply
+/// to the child. The parent's `<option>`, `<size>`, `<visual>` and
+/// `<statistic>` are kept, while the child's, along with its `<keyframe>`s,
+/// are dropped. Both documents must have the same `<compiler>` settings, since
+/// these change how the rest of the document is interpreted.
+///
+/// Both models are given as MJCF rather than as [`Model`]s, since the XML is
+/// what is combined. A compiled model can be attached by saving it with
+/// [`Model::to_xml_string()`] first. `<include>`d files are not followed, so
+/// any names in them are left as they are. References to names are recognized
+/// by their attribute: the ones MuJoCo uses for bodies, joints, geoms, sites,
+/// tendons, actuators, assets and default classes are prefixed, including in
+/// tendons, equalities, sensors and contact pairs. Other attributes, such as
+/// ones in `<custom>`, are copied unchanged.
+pub fn attach_xml(
+    parent: &str,
+    child: &str,
+    at: AttachPoint,
+    prefix: &str,
+) -> Result<String, Error> {
+    let mut parent = parse(parent)?;
+    let mut child = parse(child)?;
+
+    let parent_compiler = compiler_settings(&parent);
+    let child_compiler = compiler_settings(&child);
+    if parent_compiler != child_compiler {
+        return Err(Error::Attach(format!(
+            "<compiler> settings differ: {:?} != {:?}",
+            parent_compiler, child_compiler
+        )));
+    }
+
+    name_implicit_assets(&mut child);
+    prefix_names(&mut child, prefix);
+
+    // The child's top level default becomes a class nested in the parent's
+    let default_class = match child.take_child("default") {
+        Some(mut default) => {
+            let class = format!("{}main", prefix);
+            default.attributes.insert("class".to_owned(), class.clone());
+            section(&mut parent, "default")
+                .children
+                .push(XMLNode::Element(default));
+            Some(class)
+        }
+        None => None,
+    };
+
+    for name in MERGED_SECTIONS {
+        while let Some(mut src) = child.take_child(*name) {
+            if let Some(class) = &default_class {
+                set_default_class(&mut src, class);
+            }
+            section(&mut parent, name)
+                .children
+                .append(&mut src.children);
+        }
+    }
+
+    let mut worldbody = child
+        .take_child("worldbody")
+        .unwrap_or_else(|| Element::new("worldbody"));
+    if let Some(class) = &default_class {
+        for e in child_elements_mut(&mut worldbody) {
+            let attr = match e.name.as_str() {
+                "body" => "childclass",
+                "geom" | "site" | "camera" | "light" => "class",
+                _ => continue,
+            };
+            e.attributes
+                .entry(attr.to_owned())
+                .or_insert_with(|| class.clone());
+        }
+    }
+
+    let parent_worldbody = section(&mut parent, "worldbody");
+    match at {
+        AttachPoint::Body("world") => {
+            parent_worldbody.children.append(&mut worldbody.children)
+        }
+        AttachPoint::Body(name) => {
+            let body = find_element_mut(parent_worldbody, "body", name)
+                .ok_or_else(|| Error::Attach(format!("no body named {}", name)))?;
+            body.children.append(&mut worldbody.children);
+        }
+        AttachPoint::Site(name) => {
+            let body = find_parent_mut(parent_worldbody, "site", name)
+                .ok_or_else(|| Error::Attach(format!("no site named {}", name)))?;
+            let site = body
+                .children
+                .iter()
+                .filter_map(XMLNode::as_element)
+                .find(|e| e.name == "site" && has_name(e, name))
+                .unwrap();
+            let mut frame = Element::new("body");
+            for attr in POSE_ATTRS {
+                if let Some(value) = site.attributes.get(*attr) {
+                    frame.attributes.insert((*attr).to_owned(), value.clone());
+                }
+            }
+            frame.children.append(&mut worldbody.children);
+            body.children.push(XMLNode::Element(frame));
+        }
+    }
+
+    let mut out = Vec::new();
+    parent
+        .write(&mut out)
+        .map_err(|e| Error::Attach(e.to_string()))?;
+    Ok(String::from_utf8(out).expect("xmltree should emit UTF-8"))
+}
+
+impl Model {
+    /// Compiles `parent` with `child` attached at `at`, prefixing every name in
+    /// `child` with `prefix`. See [`attach_xml()`] for how the two are combined.
+    pub fn from_attached_xml(
+        parent: &str,
+        child: &str,
+        at: AttachPoint,
+        prefix: &str,
+    ) -> Result<Self, Error> {
+        Self::from_xml_str(attach_xml(parent, child, at, prefix)?)
+    }
+}
+
+fn parse(xml: &str) -> Result<Element, Error> {
+    let root = Element::parse(xml.as_bytes())
+        .map_err(|e| Error::from_xml_message(e.to_string()))?;
+    if root.name != "mujoco" {
+        return Err(Error::from_xml_message(format!(
+            "expected <mujoco> as the root element, found <{}>",
+            root.name
+        )));
+    }
+    Ok(root)
+}
+
+fn has_name(e: &Element, name: &str) -> bool {
+    e.attributes.get("name").map(String::as_str) == Some(name)
+}
+
+fn child_elements_mut(e: &mut Element) -> impl Iterator<Item = &mut Element> {
+    e.children.iter_mut().filter_map(XMLNode::as_mut_element)
+}
+
+/// Gets the top level section named `name`, creating it if it doesn't exist
+fn section<'a>(root: &'a mut Element, name: &str) -> &'a mut Element {
+    if root.get_child(name).is_none() {
+        root.children.push(XMLNode::Element(Element::new(name)));
+    }
+    root.get_mut_child(name).unwrap()
+}
+
+/// Collects the attributes of every `<compiler>` element
+fn compiler_settings(root: &Element) -> BTreeMap<String, String> {
+    root.children
+        .iter()
+        .filter_map(XMLNode::as_element)
+        .filter(|e| e.name == "compiler")
+        .flat_map(|e| e.attributes.clone())
+        .collect()
+}
+
+/// MuJoCo names file-based assets without a name after their file, so we make
+/// those names explicit before they are prefixed
+fn name_implicit_assets(root: &mut Element) {
+    let assets = root
+        .children
+        .iter_mut()
+        .filter_map(XMLNode::as_mut_element)
+        .filter(|e| e.name == "asset");
+    for asset in assets.flat_map(child_elements_mut) {
+        if asset.attributes.contains_key("name") {
+            continue;
+        }
+        let stem = asset
+            .attributes
+            .get("file")
+            .map(std::path::Path::new)
+            .and_then(|path| path.file_stem())
+            .and_then(|stem| stem.to_str())
+            .map(ToOwned::to_owned);
+        if let Some(stem) = stem {
+            asset.attributes.insert("name".to_owned(), stem);
+        }
+    }
+}
+
+/// Prefixes every name, and every reference to a name, in the tree
+fn prefix_names(e: &mut Element, prefix: &str) {
+    for (attr, value) in e.attributes.iter_mut() {
+        let is_name = attr == "name";
+        let is_reference = REFERENCE_ATTRS.contains(&attr.as_str());
+        if (is_name || is_reference) && value != "world" {
+            *value = format!("{}{}", prefix, value);
+        }
+    }
+    for child in child_elements_mut(e) {
+        prefix_names(child, prefix);
+    }
+}
+
+/// Sets `class` on the children of `e` that accept one and don't have one
+fn set_default_class(e: &mut Element, class: &str) {
+    for child in child_elements_mut(e) {
+        if CLASSED_ELEMENTS.contains(&child.name.as_str()) {
+            child
+                .attributes
+                .entry("class".to_owned())
+                .or_insert_with(|| class.to_owned());
+        }
+    }
+}
+
+/// Finds the element named `name` with tag `tag` in the tree
+fn find_element_mut<'a>(
+    e: &'a mut Element,
+    tag: &str,
+    name: &str,
+) -> Option<&'a mut Element> {
+    if e.name == tag && has_name(e, name) {
+        return Some(e);
+    }
+    child_elements_mut(e).find_map(|child| find_element_mut(child, tag, name))
+}
+
+/// Finds the parent of the element named `name` with tag `tag` in the tree
+fn find_parent_mut<'a>(
+    e: &'a mut Element,
+    tag: &str,
+    name: &str,
+) -> Option<&'a mut Element> {
+    let is_parent = e
+        .children
+        .iter()
+        .filter_map(XMLNode::as_element)
+        .any(|child| child.name == tag && has_name(child, name));
+    if is_parent {
+        return Some(e);
+    }
+    child_elements_mut(e).find_map(|child| find_parent_mut(child, tag, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::re_exports::ObjType;
+    use crate::{ActuatorId, BodyId, GeomId, JointId, SensorId, SiteId, TendonId};
+
+    const ARM: &str = r#"<mujoco>
+    <default>
+        <joint damping="0.5"/>
+    </default>
+    <worldbody>
+        <body name="base">
+            <joint name="shoulder" type="hinge"/>
+            <geom name="link" type="capsule" size=".05" fromto="0 0 0 0 0 .5"/>
+            <site name="wrist" pos="0 0 .5"/>
+        </body>
+    </worldbody>
+    <actuator>
+        <motor name="shoulder" joint="shoulder"/>
+    </actuator>
+</mujoco>"#;
+
+    const GRIPPER: &str = r#"<mujoco>
+    <default>
+        <joint damping="0.1"/>
+    </default>
+    <worldbody>
+        <body name="base">
+            <joint name="finger" type="slide"/>
+            <geom name="link" type="box" size=".02 .02 .02"/>
+        </body>
+    </worldbody>
+    <actuator>
+        <motor name="finger" joint="finger"/>
+    </actuator>
+    <sensor>
+        <jointpos name="finger" joint="finger"/>
+    </sensor>
+</mujoco>"#;
+
+    #[test]
+    fn attach_at_site() {
+        let m =
+            Model::from_attached_xml(ARM, GRIPPER, AttachPoint::Site("wrist"), "g/")
+                .unwrap();
+
+        // The site stays, and the child goes in an unnamed frame body next to it
+        assert_eq!(m.nbody(), 4);
+        assert!(m.name_to_id::<SiteId>("wrist").is_some());
+        assert_eq!(m.nu(), 2);
+        let finger: JointId = m.name_to_id("g/finger").unwrap();
+        assert_eq!(m.name_to_id("shoulder"), Some(JointId::new(0)));
+        assert!(m.name_to_id::<BodyId>("g/base").is_some());
+        assert!(m.name_to_id::<GeomId>("g/link").is_some());
+        assert!(m.name_to_id::<ActuatorId>("g/finger").is_some());
+        assert!(m.name_to_id::<SensorId>("g/finger").is_some());
+        assert_eq!(m.name_to_id::<ActuatorId>("finger"), None);
+
+        // Each model keeps its own defaults
+        let damping = unsafe { std::slice::from_raw_parts((*m.ptr()).dof_damping, 2) };
+        assert_eq!(damping[0], 0.5);
+        assert_eq!(damping[finger.index()], 0.1);
+    }
+
+    #[test]
+    fn attach_at_body() {
+        let xml = attach_xml(ARM, GRIPPER, AttachPoint::Body("world"), "g/").unwrap();
+        let xml = attach_xml(&xml, GRIPPER, AttachPoint::Body("base"), "h/").unwrap();
+        let m = Model::from_xml_str(xml).unwrap();
+        assert_eq!(m.nbody(), 4);
+        assert_eq!(m.nu(), 3);
+        let base: BodyId = m.name_to_id("base").unwrap();
+        let h_base: BodyId = m.name_to_id("h/base").unwrap();
+        let bodies = m.bodies();
+        assert_eq!(bodies[h_base.index()].parent_id, base);
+    }
+
+    #[test]
+    fn attach_references() {
+        let child = r#"<mujoco>
+    <worldbody>
+        <body name="left">
+            <joint name="left" type="slide"/>
+            <geom name="left" type="box" size=".02 .02 .02"/>
+            <site name="left"/>
+        </body>
+        <body name="right" pos=".1 0 0">
+            <joint name="right" type="slide"/>
+            <geom name="right" type="box" size=".02 .02 .02"/>
+            <site name="right"/>
+        </body>
+    </worldbody>
+    <tendon>
+        <spatial name="span">
+            <site site="left"/>
+            <site site="right"/>
+        </spatial>
+    </tendon>
+    <equality>
+        <joint name="mirror" joint1="left" joint2="right"/>
+    </equality>
+    <sensor>
+        <tendonpos name="span" tendon="span"/>
+    </sensor>
+    <contact>
+        <pair name="fingers" geom1="left" geom2="right"/>
+    </contact>
+</mujoco>"#;
+        // Attaching twice would fail on a repeated name if any were missed
+        let xml = attach_xml(ARM, child, AttachPoint::Body("world"), "a/").unwrap();
+        let xml = attach_xml(&xml, child, AttachPoint::Body("world"), "b/").unwrap();
+        let m = Model::from_xml_str(xml).unwrap();
+
+        for prefix in ["a/", "b/"] {
+            let name = |name: &str| format!("{}{}", prefix, name);
+            let left_joint: JointId = m.name_to_id(&name("left")).unwrap();
+            let right_joint: JointId = m.name_to_id(&name("right")).unwrap();
+            let left_geom: GeomId = m.name_to_id(&name("left")).unwrap();
+            let right_geom: GeomId = m.name_to_id(&name("right")).unwrap();
+            let left_site: SiteId = m.name_to_id(&name("left")).unwrap();
+            let tendon: TendonId = m.name_to_id(&name("span")).unwrap();
+            let sensor: SensorId = m.name_to_id(&name("span")).unwrap();
+            let eq = m.name_to_index(ObjType::EQUALITY, &name("mirror")).unwrap();
+            let pair = m.name_to_index(ObjType::PAIR, &name("fingers")).unwrap();
+            unsafe {
+                let m = &*m.ptr();
+                let wrap = *m.tendon_adr.add(tendon.index()) as usize;
+                assert_eq!(*m.wrap_objid.add(wrap), left_site.index() as i32);
+                assert_eq!(*m.eq_obj1id.add(eq), left_joint.index() as i32);
+                assert_eq!(*m.eq_obj2id.add(eq), right_joint.index() as i32);
+                assert_eq!(*m.sensor_objid.add(sensor.index()), tendon.index() as i32);
+                assert_eq!(*m.pair_geom1.add(pair), left_geom.index() as i32);
+                assert_eq!(*m.pair_geom2.add(pair), right_geom.index() as i32);
+            }
+        }
+    }
+
+    #[test]
+    fn missing_attach_point() {
+        let result = attach_xml(ARM, GRIPPER, AttachPoint::Site("elbow"), "g/");
+        assert!(matches!(result, Err(Error::Attach(_))));
+    }
+
+    #[test]
+    fn mismatched_compiler() {
+        let child = r#"<mujoco><compiler angle="radian"/></mujoco>"#;
+        let result = attach_xml(ARM, child, AttachPoint::Body("world"), "g/");
+        assert!(matches!(result, Err(Error::Attach(_))));
+    }
+}
@@ -1,36 +1,37 @@
 use std::cmp::Ordering;
 
+use crate::id::{BodyId, GeomId};
 use crate::{Geom, GeomType};
 
 use itertools::Itertools;
 use nalgebra::{Quaternion, Vector3};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Body {
-    pub id: i32,
+    pub id: BodyId,
     pub name: String,
-    pub parent_id: i32,
-    pub geom_n: i32,
-    pub geom_addr: i32,
+    pub parent_id: BodyId,
+    pub geom_n: usize,
+    /// The id of the first of the body's geoms, if it has any
+    pub geom_addr: Option<GeomId>,
     pub pos: Vector3<f64>,
     pub quat: Quaternion<f64>,
 }
 
 impl Body {
     pub fn geoms(&self, geoms: &[Geom]) -> Vec<Geom> {
-        let mut body_geoms = Vec::new();
-        for i in 0..self.geom_n {
-            let geom = &geoms[(self.geom_addr + i) as usize];
-            body_geoms.push(geom.clone());
+        match self.geom_addr {
+            Some(addr) => geoms[addr.index()..addr.index() + self.geom_n].to_vec(),
+            None => Vec::new(),
         }
-        body_geoms
     }
 
     /// Return the body to be rendered
     pub fn render_geom(&self, geoms: &[Geom]) -> Option<Geom> {
         let geom_query = geoms.iter().filter(|g| g.body_id == self.id);
         if geom_query.clone().count() == 1 {
-            return Some(geom_query.clone().last().unwrap().clone());
+            return Some(geom_query.clone().next_back().unwrap().clone());
         }
 
         let geoms = self.geoms(geoms);
@@ -0,0 +1,82 @@
+use mujoco_rs_sys::no_render::{mjContact, mjModel};
+use nalgebra::{Matrix3, Vector3};
+
+use crate::id::{index_from_raw, BodyId, GeomId};
+
+/// A contact between two geoms, as detected in the last step
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Contact {
+    /// Position of the contact in the list of contacts of the
+    /// [`Simulation`](crate::Simulation)
+    pub index: usize,
+    pub geom1: GeomId,
+    pub geom2: GeomId,
+    pub body1: BodyId,
+    pub body2: BodyId,
+    /// Position of the contact point in the world frame, midway between the
+    /// two surfaces
+    pub pos: Vector3<f64>,
+    /// Orientation of the contact frame in the world frame. The first row is
+    /// the contact normal, pointing from `geom1` to `geom2`, and the other two
+    /// rows span the tangent plane.
+    pub frame: Matrix3<f64>,
+    /// Signed distance between the surfaces, negative when penetrating
+    pub dist: f64,
+    /// Dimension of the contact: 1 for frictionless, 3 for regular friction,
+    /// 4 with torsional friction, and 6 with rolling friction
+    pub dim: usize,
+    /// Friction coefficients: 2 tangential, 1 torsional and 2 rolling
+    pub friction: [f64; 5],
+    pub solref: [f64; 2],
+    pub solimp: [f64; 5],
+    /// Offset of the contact's rows in the constraint arrays, or `None` if the
+    /// contact is excluded from the constraint solver
+    pub efc_address: Option<usize>,
+}
+
+impl Contact {
+    /// Reads contact `index` of the contacts of a simulation of `m`
+    ///
+    /// # Safety
+    /// The geom ids in `contact` must be valid for `m`.
+    pub(crate) unsafe fn new(m: &mjModel, index: usize, contact: &mjContact) -> Self {
+        let geom1 = contact.geom1 as usize;
+        let geom2 = contact.geom2 as usize;
+        Self {
+            index,
+            geom1: GeomId::new(geom1),
+            geom2: GeomId::new(geom2),
+            body1: BodyId::new(*m.geom_bodyid.add(geom1) as usize),
+            body2: BodyId::new(*m.geom_bodyid.add(geom2) as usize),
+            pos: Vector3::from(contact.pos),
+            frame: Matrix3::from_row_slice(&contact.frame),
+            dist: contact.dist,
+            dim: contact.dim as usize,
+            friction: contact.friction,
+            solref: contact.solref,
+            solimp: contact.solimp,
+            efc_address: index_from_raw(contact.efc_address),
+        }
+    }
+
+    /// Whether the contact is between geoms `a` and `b`, in either order
+    pub fn is_between_geoms(&self, a: GeomId, b: GeomId) -> bool {
+        (self.geom1, self.geom2) == (a, b) || (self.geom1, self.geom2) == (b, a)
+    }
+
+    /// Whether the contact is between bodies `a` and `b`, in either order
+    pub fn is_between_bodies(&self, a: BodyId, b: BodyId) -> bool {
+        (self.body1, self.body2) == (a, b) || (self.body1, self.body2) == (b, a)
+    }
+
+    /// Whether either side of the contact is on `body`
+    pub fn involves_body(&self, body: BodyId) -> bool {
+        self.body1 == body || self.body2 == body
+    }
+
+    /// Whether either side of the contact is `geom`
+    pub fn involves_geom(&self, geom: GeomId) -> bool {
+        self.geom1 == geom || self.geom2 == geom
+    }
+}
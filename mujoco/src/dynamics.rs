@@ -0,0 +1,232 @@
+//! The joint-space inertia matrix and inverse dynamics, for whole-body
+//! controllers.
+//!
+//! MuJoCo stores the inertia matrix `M` sparsely, along with its `L'DL`
+//! factorization, both computed in the position stage. The functions on `M`
+//! rely on them being up to date, which they are after a step or a call to
+//! [`Simulation::forward()`] or [`Simulation::fwd_position()`].
+//! [`Simulation::rne()`] also needs the velocity stage, see its documentation.
+
+use std::os::raw::c_int;
+
+use nalgebra::allocator::Allocator;
+use nalgebra::storage::Storage;
+use nalgebra::{DMatrix, DVector, DefaultAllocator, Dim, Dyn, Matrix, OMatrix};
+
+use crate::{Error, Simulation};
+
+impl Simulation {
+    /// The joint-space inertia matrix `M` as a dense, symmetric `nv`×`nv`
+    /// matrix
+    pub fn mass_matrix(&self) -> DMatrix<f64> {
+        let nv = self.model().nv();
+        let mut dense = DMatrix::zeros(nv, nv);
+        unsafe {
+            mujoco_rs_sys::no_render::mj_fullM(
+                self.model().ptr(),
+                dense.as_mut_ptr(),
+                (*self.state().ptr()).qM,
+            )
+        };
+        // The matrix is symmetric, so MuJoCo's row-major layout can be used as is
+        dense
+    }
+
+    /// Solves `M x = y` for `x`, for each column of `y`, using the sparse
+    /// factorization of `M`. `y` must have one row per degree of freedom.
+    pub fn solve_m<C: Dim, S: Storage<f64, Dyn, C>>(
+        &self,
+        y: &Matrix<f64, Dyn, C, S>,
+    ) -> Result<OMatrix<f64, Dyn, C>, Error>
+    where
+        DefaultAllocator: Allocator<f64, Dyn, C>,
+    {
+        self.check_dof_rows(y.nrows())?;
+        // The columns of an owned matrix are contiguous, which is the layout
+        // MuJoCo expects for several vectors
+        let mut x = y.clone_owned();
+        let ptr = x.as_mut_ptr();
+        unsafe {
+            mujoco_rs_sys::no_render::mj_solveM(
+                self.model().ptr(),
+                self.state().ptr(),
+                ptr,
+                ptr,
+                x.ncols() as c_int,
+            )
+        };
+        Ok(x)
+    }
+
+    /// Computes `M x` for each column of `x`, using the sparse form of `M`. `x`
+    /// must have one row per degree of freedom.
+    pub fn mul_m<C: Dim, S: Storage<f64, Dyn, C>>(
+        &self,
+        x: &Matrix<f64, Dyn, C, S>,
+    ) -> Result<OMatrix<f64, Dyn, C>, Error>
+    where
+        DefaultAllocator: Allocator<f64, Dyn, C>,
+    {
+        self.check_dof_rows(x.nrows())?;
+        // MuJoCo reads each column as a contiguous array, so a view that skips
+        // rows is copied first
+        if x.strides().0 != 1 {
+            return self.mul_m(&x.clone_owned());
+        }
+        let (rows, cols) = x.shape_generic();
+        let mut result = OMatrix::zeros_generic(rows, cols);
+        for (src, mut dst) in x.column_iter().zip(result.column_iter_mut()) {
+            unsafe {
+                mujoco_rs_sys::no_render::mj_mulM(
+                    self.model().ptr(),
+                    self.state().ptr(),
+                    dst.as_mut_ptr(),
+                    src.as_ptr(),
+                )
+            };
+        }
+        Ok(result)
+    }
+
+    /// Computes the generalized forces `M qacc + c` needed to produce the
+    /// accelerations `qacc`, where `c` are the bias forces, with the recursive
+    /// Newton-Euler algorithm. Unlike [`Simulation::inverse()`], this ignores
+    /// passive, constraint and applied forces, and leaves the state as it was.
+    ///
+    /// The bias forces depend on the velocities through the quantities
+    /// computed in the velocity stage, so this needs a step or a call to
+    /// [`Simulation::forward()`] since `qvel` was last changed.
+    /// [`Simulation::fwd_position()`] alone is not enough.
+    pub fn rne(&mut self, qacc: &[f64]) -> Result<DVector<f64>, Error> {
+        self.check_dof_rows(qacc.len())?;
+        let saved = self.qacc().to_vec();
+        self.qacc_mut().copy_from_slice(qacc);
+        let mut result = DVector::zeros(qacc.len());
+        unsafe {
+            mujoco_rs_sys::no_render::mj_rne(
+                self.model().ptr(),
+                self.state().ptr(),
+                1,
+                result.as_mut_ptr(),
+            )
+        };
+        self.qacc_mut().copy_from_slice(&saved);
+        Ok(result)
+    }
+
+    fn check_dof_rows(&self, actual: usize) -> Result<(), Error> {
+        let expected = self.model().nv();
+        if actual != expected {
+            return Err(Error::DofLength {
+                joint: None,
+                expected,
+                actual,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::SIMPLE_XML;
+    use crate::Model;
+
+    const DOUBLE_PENDULUM_XML: &str = r#"<mujoco>
+    <worldbody>
+        <body name="upper">
+            <joint name="shoulder" type="hinge" axis="0 1 0"/>
+            <geom type="capsule" size=".05" fromto="0 0 0 0 0 -1" mass="1"/>
+            <body name="lower" pos="0 0 -1">
+                <joint name="elbow" type="hinge" axis="0 1 0"/>
+                <geom type="capsule" size=".05" fromto="0 0 0 0 0 -1" mass="2"/>
+            </body>
+        </body>
+    </worldbody>
+    <actuator>
+        <motor name="motor" joint="elbow"/>
+    </actuator>
+</mujoco>"#;
+
+    fn pendulum() -> Simulation {
+        let mut sim =
+            Simulation::new(Model::from_xml_str(DOUBLE_PENDULUM_XML).unwrap());
+        sim.qpos_mut().copy_from_slice(&[0.3, -0.6]);
+        sim.qvel_mut().copy_from_slice(&[1.0, 2.0]);
+        sim.forward();
+        sim
+    }
+
+    #[test]
+    fn mass_matrix() {
+        let sim = pendulum();
+        let m = sim.mass_matrix();
+        assert_eq!(m.shape(), (2, 2));
+        assert_eq!(m, m.transpose());
+        assert!(m.clone().cholesky().is_some());
+
+        let eps = 1e-9;
+        let x = DVector::from_vec(vec![0.5, -1.5]);
+        let mx = sim.mul_m(&x).unwrap();
+        assert!((&mx - &m * &x).norm() < eps);
+        let padded = DVector::from_vec(vec![0.5, 9.0, -1.5]);
+        assert_eq!(sim.mul_m(&padded.rows_with_step(0, 2, 1)).unwrap(), mx);
+        let solved = sim.solve_m(&mx).unwrap();
+        assert!((solved - &x).norm() < eps);
+
+        // Several vectors at once
+        let identity = DMatrix::identity(2, 2);
+        let inverse = sim.solve_m(&identity).unwrap();
+        assert!((&m * inverse - identity).norm() < eps);
+
+        assert_eq!(
+            sim.mul_m(&DVector::zeros(3)),
+            Err(Error::DofLength {
+                joint: None,
+                expected: 2,
+                actual: 3
+            })
+        );
+    }
+
+    #[test]
+    fn force_decomposition() {
+        let mut sim = pendulum();
+        sim.ctrl_mut()[0] = 0.7;
+        sim.forward();
+        let eps = 1e-9;
+        assert_eq!(sim.qfrc_actuator(), &[0.0, 0.7]);
+        assert!(sim.qfrc_constraint().iter().all(|f| f.abs() < eps));
+        assert!(sim.qfrc_passive().iter().all(|f| f.abs() < eps));
+
+        // Without constraints, M qacc = actuator + passive - bias
+        let qacc = DVector::from_column_slice(sim.qacc());
+        let smooth = DVector::from_column_slice(sim.qfrc_actuator())
+            + DVector::from_column_slice(sim.qfrc_passive())
+            - DVector::from_column_slice(sim.qfrc_bias());
+        assert!((sim.mul_m(&qacc).unwrap() - smooth).norm() < eps);
+
+        // The RNE with the current accelerations reproduces the same forces
+        let qacc_before = sim.qacc().to_vec();
+        let rne = sim.rne(&qacc_before).unwrap();
+        let expected =
+            sim.mul_m(&qacc).unwrap() + DVector::from_column_slice(sim.qfrc_bias());
+        assert!((rne - expected).norm() < eps);
+        assert_eq!(sim.qacc(), qacc_before.as_slice());
+
+        // With no acceleration, only the bias forces remain
+        let bias = sim.rne(&[0.0, 0.0]).unwrap();
+        assert!((bias - DVector::from_column_slice(sim.qfrc_bias())).norm() < eps);
+    }
+
+    #[test]
+    fn free_body_mass() {
+        let sim = Simulation::new(Model::from_xml_str(*SIMPLE_XML).unwrap());
+        let m = sim.mass_matrix();
+        let mass = unsafe { *(*sim.model().ptr()).body_mass.add(1) };
+        for i in 0..3 {
+            assert!((m[(i, i)] - mass).abs() < 1e-9);
+        }
+    }
+}
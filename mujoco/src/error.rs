@@ -0,0 +1,193 @@
+use std::ffi::NulError;
+use std::path::PathBuf;
+
+use crate::vfs::AddError;
+use crate::{ActuatorId, JointId, JointType};
+
+/// An error produced while loading a [`Model`](crate::Model) or using it in a
+/// [`Simulation`](crate::Simulation)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The file to load from does not exist
+    FileNotFound(PathBuf),
+    /// The file to load from exists but could not be read
+    Io(String),
+    /// The path could not be represented as UTF-8, which MuJoCo requires
+    NonUtf8Path(PathBuf),
+    /// A string passed to MuJoCo contained a null byte in its interior
+    InteriorNul(NulError),
+    /// MuJoCo failed to parse or compile the MJCF. `line` is the line of the
+    /// offending element, if MuJoCo reported one.
+    Xml {
+        message: String,
+        line: Option<usize>,
+    },
+    /// The bytes could not be interpreted as a model in the MJB format
+    InvalidMjb,
+    /// The virtual file system has no room for another file
+    VfsFull,
+    /// A file with the same name is already present in the virtual file system
+    RepeatedName,
+    /// A filename is too long or contains a null byte
+    InvalidFileName,
+    /// MuJoCo could not save the model as MJCF XML
+    SaveXml(String),
+    /// One MJCF document could not be attached to another
+    Attach(String),
+    /// A [`Spec`](crate::spec::Spec) could not be written as MJCF
+    InvalidSpec(String),
+    /// The bytes could not be read as a zip or tar(.gz) archive. Only produced
+    /// with the `archive` feature.
+    InvalidArchive(String),
+    /// The model has no object with the given name or id
+    NoSuchObject(String),
+    /// A control vector did not have one entry per actuator
+    ControlLength { expected: usize, actual: usize },
+    /// The control for an actuator was NaN or infinite
+    NonFiniteControl(ActuatorId),
+    /// A vector did not have one entry per degree of freedom, of the joint
+    /// `joint`, or of the whole model if `joint` is `None`
+    DofLength {
+        joint: Option<JointId>,
+        expected: usize,
+        actual: usize,
+    },
+    /// A joint position or velocity was given for a joint of a different
+    /// type. `joint_type` is the actual type of the joint.
+    JointTypeMismatch {
+        joint: JointId,
+        joint_type: JointType,
+    },
+    /// A [`StateSnapshot`](crate::StateSnapshot) did not have the number of
+    /// values that its components take up in the model
+    SnapshotSize { expected: usize, actual: usize },
+}
+impl Error {
+    /// Builds an [`Error::Xml`] from the message MuJoCo wrote into its error
+    /// buffer, extracting the line number if there is one
+    pub(crate) fn from_xml_message(message: String) -> Self {
+        let line = parse_line(&message);
+        Self::Xml { message, line }
+    }
+}
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::FileNotFound(path) => {
+                write!(f, "file not found: {}", path.display())
+            }
+            Self::Io(message) => write!(f, "could not read file: {}", message),
+            Self::NonUtf8Path(path) => {
+                write!(f, "path is not valid UTF-8: {}", path.display())
+            }
+            Self::InteriorNul(e) => write!(f, "{}", e),
+            Self::Xml { message, .. } => {
+                write!(f, "invalid MJCF: {}", message.trim_end())
+            }
+            Self::InvalidMjb => f.write_str("invalid MJB model"),
+            Self::VfsFull => f.write_str("virtual file system is full"),
+            Self::RepeatedName => {
+                f.write_str("file already exists in virtual file system")
+            }
+            Self::InvalidFileName => f.write_str("invalid filename"),
+            Self::Attach(message) => write!(f, "could not attach MJCF: {}", message),
+            Self::InvalidSpec(message) => write!(f, "invalid spec: {}", message),
+            Self::SaveXml(message) => {
+                write!(f, "could not save MJCF: {}", message.trim_end())
+            }
+            Self::InvalidArchive(message) => write!(f, "invalid archive: {}", message),
+            Self::NoSuchObject(object) => write!(f, "no such object: {}", object),
+            Self::ControlLength { expected, actual } => write!(
+                f,
+                "expected {} controls, one per actuator, but got {}",
+                expected, actual
+            ),
+            Self::NonFiniteControl(actuator) => {
+                write!(f, "control for {} is not finite", actuator)
+            }
+            Self::DofLength {
+                joint: Some(joint),
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} has {} degrees of freedom, but got {} forces",
+                joint, expected, actual
+            ),
+            Self::DofLength {
+                joint: None,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "expected {} values, one per degree of freedom, but got {}",
+                expected, actual
+            ),
+            Self::JointTypeMismatch { joint, joint_type } => {
+                write!(f, "{} is a {:?} joint", joint, joint_type)
+            }
+            Self::SnapshotSize { expected, actual } => write!(
+                f,
+                "expected a snapshot of {} values for this model, but got {}",
+                expected, actual
+            ),
+        }
+    }
+}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InteriorNul(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+impl From<NulError> for Error {
+    fn from(e: NulError) -> Self {
+        Self::InteriorNul(e)
+    }
+}
+impl From<AddError> for Error {
+    fn from(e: AddError) -> Self {
+        match e {
+            AddError::VfsFull => Self::VfsFull,
+            AddError::RepeatedName => Self::RepeatedName,
+            AddError::InvalidName => Self::InvalidFileName,
+        }
+    }
+}
+
+/// Finds the line number in a MuJoCo error message. The compiler reports
+/// `line 12` while the XML parser reports `Line number=12`.
+fn parse_line(message: &str) -> Option<usize> {
+    let lower = message.to_lowercase();
+    lower.rmatch_indices("line").find_map(|(idx, _)| {
+        let rest = lower[idx + "line".len()..].trim_start_matches(" number");
+        let rest = rest.trim_start_matches([' ', '=']);
+        let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+        digits.parse().ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_line;
+
+    #[test]
+    fn line_from_compiler_message() {
+        let msg = "Error: repeated name 'geom0' in geom\nElement 'geom', line 7\n";
+        assert_eq!(parse_line(msg), Some(7));
+    }
+
+    #[test]
+    fn line_from_parser_message() {
+        let msg = "XML parse error 14:\nError=XML_ERROR_MISMATCHED_ELEMENT \
+                   ErrorID=14 (0xe) Line number=5: XMLElement name=body\n";
+        assert_eq!(parse_line(msg), Some(5));
+    }
+
+    #[test]
+    fn no_line() {
+        assert_eq!(parse_line("Error: mesh file not found"), None);
+    }
+}
@@ -1,5 +1,6 @@
 use nalgebra::{Quaternion, Vector3};
 
+use crate::id::{BodyId, GeomId};
 use crate::Mesh;
 
 use crate::re_exports::GeomType;
@@ -20,11 +21,13 @@ pub fn geom_type_from(val: usize) -> GeomType {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Geom {
-    pub id: i32,
+    pub id: GeomId,
     pub name: String,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::GeomTypeDef"))]
     pub geom_type: GeomType,
-    pub body_id: i32,
+    pub body_id: BodyId,
     pub pos: Vector3<f64>,
     pub quat: Quaternion<f64>,
     pub size: Vector3<f64>,
@@ -15,7 +15,7 @@ pub fn convert_err_buf(err_buf: Vec<u8>) -> String {
         // Will shrink vec to fit. Not ideal.
         unsafe { CString::from_vec_unchecked(err_buf) }
     });
-    err_str.into_string().expect("`CString` was not UTF-8!")
+    err_str.to_string_lossy().into_owned()
 }
 
 pub(crate) struct Local<T>(T);
@@ -126,3 +126,44 @@ where
 
     result_vec
 }
+
+/// Borrows `len` values starting at `ptr` as a slice, for a lifetime that the
+/// caller ties to the owner of the array
+///
+/// # Safety
+/// Unless `len` is 0, `ptr` must point to `len` initialized values that live
+/// and are not mutated for `'a`.
+pub(crate) unsafe fn borrow_slice<'a, T>(ptr: *const T, len: usize) -> &'a [T] {
+    // MuJoCo leaves empty arrays as null pointers
+    if len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(ptr, len)
+    }
+}
+
+/// Mutable counterpart of [`borrow_slice()`]
+///
+/// # Safety
+/// Unless `len` is 0, `ptr` must point to `len` initialized values that live
+/// and are not otherwise accessed for `'a`.
+pub(crate) unsafe fn borrow_slice_mut<'a, T>(ptr: *mut T, len: usize) -> &'a mut [T] {
+    if len == 0 {
+        &mut []
+    } else {
+        std::slice::from_raw_parts_mut(ptr, len)
+    }
+}
+
+/// Copies entry `i` of an `mjModel` array with `N` values per entry
+///
+/// # Safety
+/// `ptr` must point to at least `N * (i + 1)` initialized values.
+pub(crate) unsafe fn read_array<T: Copy + Default, const N: usize>(
+    ptr: *const T,
+    i: usize,
+) -> [T; N] {
+    let mut out = [T::default(); N];
+    out.copy_from_slice(std::slice::from_raw_parts(ptr.add(N * i), N));
+    out
+}
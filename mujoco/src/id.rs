@@ -0,0 +1,151 @@
+//! Strongly typed ids for the objects in a [`Model`](crate::Model).
+//!
+//! Each kind of object has its own id type, so that for example a [`JointId`]
+//! cannot be used where a [`BodyId`] is expected. Ids are offsets into the
+//! arrays of the underlying `mjModel` and `mjData`, and are only meaningful for
+//! the model that they came from.
+
+use std::convert::TryFrom;
+
+use crate::re_exports::ObjType;
+use crate::Model;
+
+/// An id of a kind of object in a [`Model`](crate::Model)
+pub trait ObjectId:
+    Copy + Eq + std::hash::Hash + std::fmt::Debug + std::fmt::Display
+{
+    /// The kind of object that this id refers to
+    const OBJ_TYPE: ObjType;
+
+    /// Creates an id from an offset into the arrays for its kind of object
+    fn new(index: usize) -> Self;
+
+    /// The offset into the arrays for this kind of object
+    fn index(self) -> usize;
+}
+
+macro_rules! object_id {
+    ($(#[$doc:meta])* $name:ident, $obj_type:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub struct $name(usize);
+        impl $name {
+            /// Creates an id from an offset into the arrays for its kind of
+            /// object
+            pub const fn new(index: usize) -> Self {
+                Self(index)
+            }
+
+            /// The offset into the arrays for this kind of object
+            pub const fn index(self) -> usize {
+                self.0
+            }
+        }
+        impl ObjectId for $name {
+            const OBJ_TYPE: ObjType = ObjType::$obj_type;
+
+            fn new(index: usize) -> Self {
+                Self(index)
+            }
+
+            fn index(self) -> usize {
+                self.0
+            }
+        }
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "{}({})", stringify!($name), self.0)
+            }
+        }
+    };
+}
+
+object_id!(
+    /// The id of a body. The world body always has id 0.
+    BodyId,
+    BODY
+);
+object_id!(
+    /// The id of a joint
+    JointId,
+    JOINT
+);
+object_id!(
+    /// The id of a geom
+    GeomId,
+    GEOM
+);
+object_id!(
+    /// The id of a site
+    SiteId,
+    SITE
+);
+object_id!(
+    /// The id of a camera
+    CameraId,
+    CAMERA
+);
+object_id!(
+    /// The id of a light
+    LightId,
+    LIGHT
+);
+object_id!(
+    /// The id of a mesh
+    MeshId,
+    MESH
+);
+object_id!(
+    /// The id of a tendon
+    TendonId,
+    TENDON
+);
+object_id!(
+    /// The id of an actuator
+    ActuatorId,
+    ACTUATOR
+);
+object_id!(
+    /// The id of a sensor
+    SensorId,
+    SENSOR
+);
+object_id!(
+    /// The id of a keyframe
+    KeyId,
+    KEY
+);
+
+impl BodyId {
+    /// The id of the world body
+    pub const WORLD: Self = Self(0);
+}
+
+/// Identifies an object of kind `I` in a [`Model`], either by its id or by its
+/// name. Functions that take an `impl ObjectRef<I>` accept both.
+pub trait ObjectRef<I: ObjectId>: std::fmt::Display {
+    /// Resolves to an id, or `None` if `model` has no such object
+    fn resolve(&self, model: &Model) -> Option<I>;
+}
+
+impl<I: ObjectId> ObjectRef<I> for I {
+    fn resolve(&self, model: &Model) -> Option<I> {
+        if self.index() < model.count(I::OBJ_TYPE) {
+            Some(*self)
+        } else {
+            None
+        }
+    }
+}
+
+impl<I: ObjectId> ObjectRef<I> for &str {
+    fn resolve(&self, model: &Model) -> Option<I> {
+        model.name_to_id(self)
+    }
+}
+
+/// Converts an offset read from an `mjModel` array, where -1 means no object
+pub(crate) fn index_from_raw(raw: std::os::raw::c_int) -> Option<usize> {
+    usize::try_from(raw).ok()
+}
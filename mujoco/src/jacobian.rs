@@ -0,0 +1,279 @@
+//! Jacobians of points and frames on bodies with respect to the generalized
+//! velocities.
+
+use std::os::raw::c_int;
+
+use mujoco_rs_sys::no_render::{mjData, mjModel};
+use nalgebra::{Matrix3xX, Point3, Vector3};
+
+use crate::{BodyId, Error, GeomId, ObjectId, ObjectRef, Simulation, SiteId};
+
+/// Maps the generalized velocities of a [`Simulation`] to the velocity of a
+/// point or frame in the world frame, with one column per degree of freedom
+#[derive(Debug, Clone, PartialEq)]
+pub struct Jacobian {
+    /// Maps to the linear velocity of the point
+    pub translation: Matrix3xX<f64>,
+    /// Maps to the angular velocity of the body that the point is on
+    pub rotation: Matrix3xX<f64>,
+}
+
+impl Jacobian {
+    /// Fills a pair of row-major 3×nv arrays with `f` and converts them
+    fn compute(nv: usize, f: impl FnOnce(*mut f64, *mut f64)) -> Self {
+        let mut jacp = vec![0.0; 3 * nv];
+        let mut jacr = vec![0.0; 3 * nv];
+        f(jacp.as_mut_ptr(), jacr.as_mut_ptr());
+        Self {
+            translation: Matrix3xX::from_row_slice(&jacp),
+            rotation: Matrix3xX::from_row_slice(&jacr),
+        }
+    }
+}
+
+/// The Jacobians of a point and of a direction attached to a body, as computed
+/// by [`Simulation::point_axis_jacobian()`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointAxisJacobian {
+    /// Maps to the linear velocity of the point
+    pub point: Matrix3xX<f64>,
+    /// Maps to the rate of change of the direction
+    pub axis: Matrix3xX<f64>,
+}
+
+/// The step in time used to differentiate Jacobians numerically
+const JACOBIAN_DOT_STEP: f64 = 1e-6;
+
+impl Simulation {
+    fn raw(&self) -> (*const mjModel, *mut mjData) {
+        (self.model().ptr(), self.state().ptr())
+    }
+
+    fn jacobian_of<I: ObjectId>(
+        &self,
+        obj: impl ObjectRef<I>,
+        f: unsafe extern "C" fn(
+            *const mjModel,
+            *const mjData,
+            *mut f64,
+            *mut f64,
+            c_int,
+        ),
+    ) -> Result<Jacobian, Error> {
+        let id = self.resolve(obj)?;
+        let (m, d) = self.raw();
+        Ok(Jacobian::compute(self.model().nv(), |jacp, jacr| unsafe {
+            f(m, d, jacp, jacr, id.index() as c_int)
+        }))
+    }
+
+    /// The Jacobian of `point`, given in the world frame, as if it were
+    /// attached to `body`. Like every Jacobian, it depends on the kinematics
+    /// computed by the last step or call to [`Simulation::forward()`] or
+    /// [`Simulation::kinematics()`] followed by [`Simulation::com_pos()`].
+    pub fn jacobian(
+        &self,
+        body: impl ObjectRef<BodyId>,
+        point: Vector3<f64>,
+    ) -> Result<Jacobian, Error> {
+        Ok(self.point_jacobian(self.resolve(body)?, point))
+    }
+
+    /// [`Simulation::jacobian()`] for a body that has already been resolved
+    fn point_jacobian(&self, body: BodyId, point: Vector3<f64>) -> Jacobian {
+        let (m, d) = self.raw();
+        Jacobian::compute(self.model().nv(), |jacp, jacr| unsafe {
+            mujoco_rs_sys::no_render::mj_jac(
+                m,
+                d,
+                jacp,
+                jacr,
+                point.as_ptr(),
+                body.index() as c_int,
+            )
+        })
+    }
+
+    /// The Jacobian of the origin of the frame of `body`
+    pub fn body_jacobian(
+        &self,
+        body: impl ObjectRef<BodyId>,
+    ) -> Result<Jacobian, Error> {
+        self.jacobian_of(body, mujoco_rs_sys::no_render::mj_jacBody)
+    }
+
+    /// The Jacobian of the center of mass of `body`
+    pub fn body_com_jacobian(
+        &self,
+        body: impl ObjectRef<BodyId>,
+    ) -> Result<Jacobian, Error> {
+        self.jacobian_of(body, mujoco_rs_sys::no_render::mj_jacBodyCom)
+    }
+
+    /// The Jacobian of the frame of `site`
+    pub fn site_jacobian(
+        &self,
+        site: impl ObjectRef<SiteId>,
+    ) -> Result<Jacobian, Error> {
+        self.jacobian_of(site, mujoco_rs_sys::no_render::mj_jacSite)
+    }
+
+    /// The Jacobian of the frame of `geom`
+    pub fn geom_jacobian(
+        &self,
+        geom: impl ObjectRef<GeomId>,
+    ) -> Result<Jacobian, Error> {
+        self.jacobian_of(geom, mujoco_rs_sys::no_render::mj_jacGeom)
+    }
+
+    /// The Jacobians of `point` and of the direction `axis`, both given in the
+    /// world frame, as if they were attached to `body`
+    pub fn point_axis_jacobian(
+        &self,
+        body: impl ObjectRef<BodyId>,
+        point: Vector3<f64>,
+        axis: Vector3<f64>,
+    ) -> Result<PointAxisJacobian, Error> {
+        let body = self.resolve(body)?;
+        let (m, d) = self.raw();
+        let jacobian =
+            Jacobian::compute(self.model().nv(), |jac_point, jac_axis| unsafe {
+                mujoco_rs_sys::no_render::mj_jacPointAxis(
+                    m,
+                    d,
+                    jac_point,
+                    jac_axis,
+                    point.as_ptr(),
+                    axis.as_ptr(),
+                    body.index() as c_int,
+                )
+            });
+        Ok(PointAxisJacobian {
+            point: jacobian.translation,
+            axis: jacobian.rotation,
+        })
+    }
+
+    /// The time derivative of [`Simulation::jacobian()`] at the current
+    /// positions and velocities, with `point` moving along with `body`.
+    ///
+    /// MuJoCo 2.3 has no `mj_jacDot`, so this is computed by central finite
+    /// differences: the positions are integrated a small step forwards and
+    /// backwards along the current velocities in a copy of the state, which
+    /// costs a copy of the state and two evaluations of the kinematics. With
+    /// the step of `1e-6` used, the truncation error is negligible and rounding
+    /// dominates: expect an absolute error of around `1e-10` times the
+    /// magnitude of the entries of the Jacobian.
+    pub fn jacobian_dot(
+        &self,
+        body: impl ObjectRef<BodyId>,
+        point: Vector3<f64>,
+    ) -> Result<Jacobian, Error> {
+        let body = self.resolve(body)?;
+        let local = self
+            .body_pose_of(body)
+            .inverse_transform_point(&Point3::from(point));
+
+        let h = JACOBIAN_DOT_STEP;
+        let qpos = self.qpos().to_vec();
+        let mut scratch = Simulation::from_state(self.state().clone());
+        let mut jacobian_at = |dt: f64| {
+            scratch.qpos_mut().copy_from_slice(&qpos);
+            let (m, d) = scratch.raw();
+            unsafe {
+                mujoco_rs_sys::no_render::mj_integratePos(m, (*d).qpos, (*d).qvel, dt)
+            };
+            scratch.kinematics();
+            scratch.com_pos();
+            let point = scratch.body_pose_of(body) * local;
+            scratch.point_jacobian(body, point.coords)
+        };
+        let ahead = jacobian_at(h / 2.0);
+        let behind = jacobian_at(-h / 2.0);
+        Ok(Jacobian {
+            translation: (ahead.translation - behind.translation) / h,
+            rotation: (ahead.rotation - behind.rotation) / h,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::SIMPLE_XML;
+    use crate::Model;
+    use nalgebra::{Matrix3, Vector6};
+
+    const ARM_XML: &str = r#"<mujoco>
+    <worldbody>
+        <body name="arm">
+            <joint name="hinge" type="hinge" axis="0 0 1"/>
+            <geom name="rod" type="capsule" size=".05" fromto="0 0 0 2 0 0"/>
+            <site name="tip" pos="2 0 0"/>
+        </body>
+    </worldbody>
+</mujoco>"#;
+
+    #[test]
+    fn free_body() {
+        let sim = Simulation::new(Model::from_xml_str(*SIMPLE_XML).unwrap());
+        let jacobian = sim.body_jacobian("body1").unwrap();
+        assert_eq!(jacobian.translation.ncols(), 6);
+
+        // The linear velocities are in the world frame, and the angular ones
+        // in the frame of the body, which is aligned with the world
+        let eps = 1e-9;
+        let translation = jacobian.translation.columns(0, 3).into_owned();
+        assert!((translation - Matrix3::identity()).norm() < eps);
+        assert!(jacobian.translation.columns(3, 3).norm() < eps);
+        let rotation = jacobian.rotation.columns(3, 3).into_owned();
+        assert!((rotation - Matrix3::identity()).norm() < eps);
+
+        // The geom and center of mass coincide with the body's origin
+        let geom = sim.geom_jacobian("geom1").unwrap();
+        assert!((geom.translation - &jacobian.translation).norm() < eps);
+        let com = sim.body_com_jacobian(BodyId::new(1)).unwrap();
+        assert!((com.translation - &jacobian.translation).norm() < eps);
+
+        // A point away from the origin also moves with the rotation
+        let point = Vector3::new(1.0, 0.0, 1.0);
+        let jacobian = sim.jacobian("body1", point).unwrap();
+        let qvel = Vector6::new(0.0, 0.0, 0.0, 0.0, 0.0, 1.0);
+        let velocity = &jacobian.translation * qvel;
+        assert!((velocity - Vector3::new(0.0, 1.0, 0.0)).norm() < eps);
+
+        assert_eq!(
+            sim.body_jacobian("missing"),
+            Err(Error::NoSuchObject("missing".to_owned()))
+        );
+    }
+
+    #[test]
+    fn rotating_arm() {
+        let mut sim = Simulation::new(Model::from_xml_str(ARM_XML).unwrap());
+        let eps = 1e-9;
+        let jacobian = sim.site_jacobian("tip").unwrap();
+        assert!(
+            (jacobian.translation.column(0) - Vector3::new(0.0, 2.0, 0.0)).norm() < eps
+        );
+        assert!((jacobian.rotation.column(0) - Vector3::z()).norm() < eps);
+
+        let point_axis = sim
+            .point_axis_jacobian("arm", Vector3::new(2.0, 0.0, 0.0), Vector3::x())
+            .unwrap();
+        assert!((point_axis.point - &jacobian.translation).norm() < eps);
+        assert!((point_axis.axis.column(0) - Vector3::y()).norm() < eps);
+
+        // The tip moves on a circle, so its Jacobian turns with the arm
+        sim.qvel_mut()[0] = 3.0;
+        let jacobian_dot = sim
+            .jacobian_dot("arm", Vector3::new(2.0, 0.0, 0.0))
+            .unwrap();
+        let expected = Vector3::new(-6.0, 0.0, 0.0);
+        assert!((jacobian_dot.translation.column(0) - expected).norm() < 1e-8);
+        assert!(jacobian_dot.rotation.norm() < 1e-8);
+
+        // Computing it leaves the simulation untouched
+        assert_eq!(sim.qpos(), &[0.0]);
+    }
+}
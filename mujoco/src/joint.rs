@@ -0,0 +1,223 @@
+use std::os::raw::c_int;
+
+use mujoco_rs_sys::no_render::mjModel;
+use nalgebra::{Quaternion, UnitQuaternion, Vector3};
+
+use crate::helpers::read_array;
+use crate::id::{BodyId, JointId};
+use crate::re_exports::JointType;
+
+pub fn joint_type_from(val: c_int) -> JointType {
+    [
+        JointType::FREE,
+        JointType::BALL,
+        JointType::SLIDE,
+        JointType::HINGE,
+    ]
+    .iter()
+    .copied()
+    .find(|&t| t as c_int == val)
+    .expect("Invalid value for JointType")
+}
+
+/// The number of generalized coordinates a joint of type `joint_type` has
+pub fn joint_nq(joint_type: JointType) -> usize {
+    match joint_type {
+        JointType::FREE => 7,
+        JointType::BALL => 4,
+        JointType::SLIDE | JointType::HINGE => 1,
+    }
+}
+
+/// The number of degrees of freedom a joint of type `joint_type` has
+pub fn joint_nv(joint_type: JointType) -> usize {
+    match joint_type {
+        JointType::FREE => 6,
+        JointType::BALL => 3,
+        JointType::SLIDE | JointType::HINGE => 1,
+    }
+}
+
+/// A joint of a [`Model`](crate::Model)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Joint {
+    pub id: JointId,
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::JointTypeDef"))]
+    pub joint_type: JointType,
+    /// The body that the joint moves relative to its parent
+    pub body_id: BodyId,
+    /// Offset of the joint's coordinates in [`Simulation::qpos()`]
+    ///
+    /// [`Simulation::qpos()`]: crate::Simulation::qpos
+    pub qpos_addr: usize,
+    /// Offset of the joint's degrees of freedom in [`Simulation::qvel()`]
+    ///
+    /// [`Simulation::qvel()`]: crate::Simulation::qvel
+    pub dof_addr: usize,
+    pub limited: bool,
+    /// Only meaningful if the joint is `limited`. For ball joints, only the
+    /// upper bound is used, as the maximum rotation angle.
+    pub range: [f64; 2],
+    /// Position of the joint in the frame of its body
+    pub pos: Vector3<f64>,
+    /// Axis of hinge and slide joints in the frame of its body
+    pub axis: Vector3<f64>,
+    pub stiffness: f64,
+    /// Damping of the joint's first degree of freedom
+    pub damping: f64,
+    /// Armature inertia of the joint's first degree of freedom
+    pub armature: f64,
+}
+
+impl Joint {
+    /// Reads joint `i` out of `m`
+    ///
+    /// # Safety
+    /// `i` must be less than `m.njnt`.
+    pub(crate) unsafe fn from_model(m: &mjModel, i: usize, name: &str) -> Self {
+        let dof_addr = *m.jnt_dofadr.add(i) as usize;
+        Self {
+            id: JointId::new(i),
+            name: name.to_owned(),
+            joint_type: joint_type_from(*m.jnt_type.add(i)),
+            body_id: BodyId::new(*m.jnt_bodyid.add(i) as usize),
+            qpos_addr: *m.jnt_qposadr.add(i) as usize,
+            dof_addr,
+            limited: *m.jnt_limited.add(i) != 0,
+            range: read_array(m.jnt_range, i),
+            pos: Vector3::from(read_array::<f64, 3>(m.jnt_pos, i)),
+            axis: Vector3::from(read_array::<f64, 3>(m.jnt_axis, i)),
+            stiffness: *m.jnt_stiffness.add(i),
+            damping: *m.dof_damping.add(dof_addr),
+            armature: *m.dof_armature.add(dof_addr),
+        }
+    }
+
+    /// The number of generalized coordinates the joint has in `qpos`
+    pub fn nq(&self) -> usize {
+        joint_nq(self.joint_type)
+    }
+
+    /// The number of degrees of freedom the joint has in `qvel`
+    pub fn nv(&self) -> usize {
+        joint_nv(self.joint_type)
+    }
+}
+
+/// The generalized coordinates of a single joint
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum JointPosition {
+    /// Position and orientation of the body in the world frame
+    Free {
+        pos: Vector3<f64>,
+        quat: UnitQuaternion<f64>,
+    },
+    /// Orientation of the body relative to its parent
+    Ball(UnitQuaternion<f64>),
+    /// Displacement along the axis
+    Slide(f64),
+    /// Rotation about the axis, in radians
+    Hinge(f64),
+}
+
+impl JointPosition {
+    /// Reads the position of a joint of type `joint_type` from its slice of
+    /// `qpos`
+    pub(crate) fn from_qpos(joint_type: JointType, qpos: &[f64]) -> Self {
+        let quat = |q: &[f64]| {
+            UnitQuaternion::new_normalize(Quaternion::new(q[0], q[1], q[2], q[3]))
+        };
+        match joint_type {
+            JointType::FREE => Self::Free {
+                pos: Vector3::from_column_slice(&qpos[..3]),
+                quat: quat(&qpos[3..]),
+            },
+            JointType::BALL => Self::Ball(quat(qpos)),
+            JointType::SLIDE => Self::Slide(qpos[0]),
+            JointType::HINGE => Self::Hinge(qpos[0]),
+        }
+    }
+
+    /// The type of joint that this is a position of
+    pub fn joint_type(&self) -> JointType {
+        match self {
+            Self::Free { .. } => JointType::FREE,
+            Self::Ball(_) => JointType::BALL,
+            Self::Slide(_) => JointType::SLIDE,
+            Self::Hinge(_) => JointType::HINGE,
+        }
+    }
+
+    /// Writes the position into a joint's slice of `qpos`, with quaternions
+    /// in MuJoCo's `w, x, y, z` order
+    pub(crate) fn write_qpos(&self, qpos: &mut [f64]) {
+        let write_quat = |q: &UnitQuaternion<f64>, out: &mut [f64]| {
+            out.copy_from_slice(&[q.w, q.i, q.j, q.k]);
+        };
+        match self {
+            Self::Free { pos, quat } => {
+                qpos[..3].copy_from_slice(pos.as_slice());
+                write_quat(quat, &mut qpos[3..]);
+            }
+            Self::Ball(quat) => write_quat(quat, qpos),
+            Self::Slide(x) | Self::Hinge(x) => qpos[0] = *x,
+        }
+    }
+}
+
+/// The generalized velocities of a single joint
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum JointVelocity {
+    /// Linear velocity in the world frame, and angular velocity in the frame of
+    /// the body
+    Free {
+        linear: Vector3<f64>,
+        angular: Vector3<f64>,
+    },
+    /// Angular velocity in the frame of the body
+    Ball(Vector3<f64>),
+    Slide(f64),
+    Hinge(f64),
+}
+
+impl JointVelocity {
+    /// Reads the velocity of a joint of type `joint_type` from its slice of
+    /// `qvel`
+    pub(crate) fn from_qvel(joint_type: JointType, qvel: &[f64]) -> Self {
+        match joint_type {
+            JointType::FREE => Self::Free {
+                linear: Vector3::from_column_slice(&qvel[..3]),
+                angular: Vector3::from_column_slice(&qvel[3..]),
+            },
+            JointType::BALL => Self::Ball(Vector3::from_column_slice(qvel)),
+            JointType::SLIDE => Self::Slide(qvel[0]),
+            JointType::HINGE => Self::Hinge(qvel[0]),
+        }
+    }
+
+    /// The type of joint that this is a velocity of
+    pub fn joint_type(&self) -> JointType {
+        match self {
+            Self::Free { .. } => JointType::FREE,
+            Self::Ball(_) => JointType::BALL,
+            Self::Slide(_) => JointType::SLIDE,
+            Self::Hinge(_) => JointType::HINGE,
+        }
+    }
+
+    /// Writes the velocity into a joint's slice of `qvel`
+    pub(crate) fn write_qvel(&self, qvel: &mut [f64]) {
+        match self {
+            Self::Free { linear, angular } => {
+                qvel[..3].copy_from_slice(linear.as_slice());
+                qvel[3..].copy_from_slice(angular.as_slice());
+            }
+            Self::Ball(angular) => qvel.copy_from_slice(angular.as_slice()),
+            Self::Slide(v) | Self::Hinge(v) => qvel[0] = *v,
+        }
+    }
+}
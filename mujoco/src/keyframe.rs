@@ -0,0 +1,205 @@
+use mujoco_rs_sys::no_render::{mjData, mjModel};
+use xmltree::{Element, XMLNode};
+
+use crate::helpers::borrow_slice;
+use crate::Error;
+
+/// A keyframe of a [`Model`](crate::Model), as defined by a `<key>` element of
+/// MJCF. Simulations can be reset to one with
+/// [`Simulation::reset_to_keyframe()`].
+///
+/// [`Simulation::reset_to_keyframe()`]: crate::Simulation::reset_to_keyframe
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Keyframe {
+    pub name: String,
+    pub time: f64,
+    pub qpos: Vec<f64>,
+    pub qvel: Vec<f64>,
+    pub act: Vec<f64>,
+    pub ctrl: Vec<f64>,
+    /// Positions of the mocap bodies, 3 values each
+    pub mocap_pos: Vec<f64>,
+    /// Orientations of the mocap bodies, as quaternions of 4 values each
+    pub mocap_quat: Vec<f64>,
+}
+
+impl Keyframe {
+    /// Reads keyframe `i` out of `m`
+    ///
+    /// # Safety
+    /// `i` must be less than `m.nkey`.
+    pub(crate) unsafe fn from_model(m: &mjModel, i: usize, name: &str) -> Self {
+        let row = |ptr: *const f64, n: i32| {
+            let n = n as usize;
+            borrow_slice(ptr.add(i * n), n).to_vec()
+        };
+        Self {
+            name: name.to_owned(),
+            time: *m.key_time.add(i),
+            qpos: row(m.key_qpos, m.nq),
+            qvel: row(m.key_qvel, m.nv),
+            act: row(m.key_act, m.na),
+            ctrl: row(m.key_ctrl, m.nu),
+            mocap_pos: row(m.key_mpos, 3 * m.nmocap),
+            mocap_quat: row(m.key_mquat, 4 * m.nmocap),
+        }
+    }
+
+    /// Captures the current state in `d` as a keyframe named `name`
+    ///
+    /// # Safety
+    /// `d` must be a valid `mjData` made for `m`.
+    pub(crate) unsafe fn from_data(m: &mjModel, d: &mjData, name: &str) -> Self {
+        let values = |ptr: *const f64, n: i32| borrow_slice(ptr, n as usize).to_vec();
+        Self {
+            name: name.to_owned(),
+            time: d.time,
+            qpos: values(d.qpos, m.nq),
+            qvel: values(d.qvel, m.nv),
+            act: values(d.act, m.na),
+            ctrl: values(d.ctrl, m.nu),
+            mocap_pos: values(d.mocap_pos, 3 * m.nmocap),
+            mocap_quat: values(d.mocap_quat, 4 * m.nmocap),
+        }
+    }
+
+    /// Builds the `<key>` element for the keyframe. Empty arrays are left out,
+    /// so that MuJoCo fills them with their defaults.
+    fn to_element(&self) -> Element {
+        let mut key = Element::new("key");
+        let mut set = |attr: &str, value: String| {
+            key.attributes.insert(attr.to_owned(), value);
+        };
+        if !self.name.is_empty() {
+            set("name", self.name.clone());
+        }
+        set("time", self.time.to_string());
+        for (attr, values) in [
+            ("qpos", &self.qpos),
+            ("qvel", &self.qvel),
+            ("act", &self.act),
+            ("ctrl", &self.ctrl),
+            ("mpos", &self.mocap_pos),
+            ("mquat", &self.mocap_quat),
+        ] {
+            if !values.is_empty() {
+                let values: Vec<_> = values.iter().map(f64::to_string).collect();
+                set(attr, values.join(" "));
+            }
+        }
+        key
+    }
+}
+
+/// Adds `keyframes` to the `<keyframe>` section of the MJCF in `xml`, after
+/// any that are already there. `xml` is expected to come from MuJoCo, so
+/// failing to parse it counts as failing to save.
+pub(crate) fn add_keyframes_xml(
+    xml: &str,
+    keyframes: &[Keyframe],
+) -> Result<String, Error> {
+    let mut root =
+        Element::parse(xml.as_bytes()).map_err(|e| Error::SaveXml(e.to_string()))?;
+    if root.get_child("keyframe").is_none() {
+        root.children
+            .push(XMLNode::Element(Element::new("keyframe")));
+    }
+    let section = root.get_mut_child("keyframe").unwrap();
+    section.children.extend(
+        keyframes
+            .iter()
+            .map(|keyframe| XMLNode::Element(keyframe.to_element())),
+    );
+
+    let mut out = Vec::new();
+    root.write(&mut out)
+        .map_err(|e| Error::SaveXml(e.to_string()))?;
+    Ok(String::from_utf8(out).expect("xmltree should emit UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KeyId, Model, Simulation};
+
+    const KEYFRAME_XML: &str = r#"<mujoco>
+    <worldbody>
+        <body name="target" mocap="true" pos="1 0 0"/>
+        <body name="pendulum">
+            <joint name="hinge" type="hinge" axis="0 1 0"/>
+            <geom type="capsule" size=".05" fromto="0 0 0 0 0 -1"/>
+        </body>
+    </worldbody>
+    <actuator>
+        <motor name="motor" joint="hinge"/>
+    </actuator>
+    <keyframe>
+        <key name="raised" time="1.5" qpos="1.2" qvel="-0.5" ctrl="0.3"/>
+        <key qpos="0.1"/>
+    </keyframe>
+</mujoco>"#;
+
+    #[test]
+    fn keyframes() {
+        let m = Model::from_xml_str(KEYFRAME_XML).unwrap();
+        let keyframes = m.keyframes();
+        assert_eq!(keyframes.len(), 2);
+        assert_eq!(m.name_to_id("raised"), Some(KeyId::new(0)));
+
+        let raised = &keyframes[0];
+        assert_eq!(raised.name, "raised");
+        assert_eq!(raised.time, 1.5);
+        assert_eq!(raised.qpos, vec![1.2]);
+        assert_eq!(raised.qvel, vec![-0.5]);
+        assert!(raised.act.is_empty());
+        assert_eq!(raised.ctrl, vec![0.3]);
+        assert_eq!(raised.mocap_pos, vec![1.0, 0.0, 0.0]);
+        assert_eq!(raised.mocap_quat, vec![1.0, 0.0, 0.0, 0.0]);
+
+        assert_eq!(keyframes[1].name, "");
+        assert_eq!(keyframes[1].qpos, vec![0.1]);
+    }
+
+    #[test]
+    fn reset_to_keyframe() {
+        let mut sim = Simulation::new(Model::from_xml_str(KEYFRAME_XML).unwrap());
+        sim.reset_to_keyframe("raised").unwrap();
+        assert_eq!(sim.state().time(), 1.5);
+        assert_eq!(sim.qpos(), &[1.2]);
+        assert_eq!(sim.qvel(), &[-0.5]);
+        assert_eq!(sim.ctrl(), &[0.3]);
+
+        sim.reset_to_keyframe(KeyId::new(1)).unwrap();
+        assert_eq!(sim.state().time(), 0.0);
+        assert_eq!(sim.qpos(), &[0.1]);
+
+        assert_eq!(
+            sim.reset_to_keyframe("missing"),
+            Err(Error::NoSuchObject("missing".to_owned()))
+        );
+        assert_eq!(
+            sim.reset_to_keyframe(KeyId::new(2)),
+            Err(Error::NoSuchObject("KeyId(2)".to_owned()))
+        );
+    }
+
+    #[test]
+    fn add_keyframes() {
+        let m = Model::from_xml_str(KEYFRAME_XML).unwrap();
+        let mut sim = Simulation::new(m);
+        sim.reset_to_keyframe("raised").unwrap();
+        for _ in 0..10 {
+            sim.step();
+        }
+        let captured = sim.capture_keyframe("captured");
+        assert_eq!(captured.qpos, sim.qpos());
+
+        let xml =
+            add_keyframes_xml(KEYFRAME_XML, std::slice::from_ref(&captured)).unwrap();
+        let m = Model::from_xml_str(&xml).unwrap();
+        let keyframes = m.keyframes();
+        assert_eq!(keyframes.len(), 3);
+        assert_eq!(keyframes[2], captured);
+    }
+}
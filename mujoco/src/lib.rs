@@ -1,22 +1,59 @@
 //! Provides safe bindings to [MuJoCo](http://www.mujoco.org/index.html), a physics
 //! simulator commonly used for robotics and machine learning.
 
+pub mod actuator;
+#[cfg(feature = "archive")]
+mod archive;
+pub mod attach;
 pub mod body;
+pub mod contact;
+pub mod dynamics;
+pub mod error;
 pub mod geom;
+pub mod id;
+pub mod jacobian;
+pub mod joint;
+pub mod keyframe;
 pub mod mesh;
 pub mod model;
+mod names;
+pub mod options;
 mod re_exports;
+pub mod sensor;
+#[cfg(feature = "serde")]
+mod serde_impls;
 pub mod sim;
+pub mod snapshot;
+pub mod spec;
 pub mod state;
-mod vfs;
+pub mod vfs;
 
+pub use actuator::{Actuator, Transmission};
+pub use attach::AttachPoint;
 pub use body::Body;
+pub use contact::Contact;
+pub use error::Error;
 pub use geom::Geom;
+pub use id::{
+    ActuatorId, BodyId, CameraId, GeomId, JointId, KeyId, LightId, MeshId, ObjectId,
+    ObjectRef, SensorId, SiteId, TendonId,
+};
+pub use jacobian::{Jacobian, PointAxisJacobian};
+pub use joint::{Joint, JointPosition, JointVelocity};
+pub use keyframe::Keyframe;
 pub use mesh::Mesh;
 pub use model::Model;
-pub use re_exports::GeomType;
+pub use options::PhysicsOptions;
+pub use re_exports::{
+    BiasType, Cone, DisableBit, DynType, EnableBit, GainType, GeomType, Integrator,
+    JointType, SensorDataType, SensorType, Solver, Stage,
+};
+pub use sensor::{Sensor, SensorObject, SensorValue};
 pub use sim::Simulation;
+pub use snapshot::{Checkpoint, StateMask, StateSnapshot};
+pub use spec::Spec;
 pub use state::State;
+pub use vfs::Vfs;
 
 use std::cell::RefCell;
 
@@ -1,8 +1,18 @@
+use crate::id::{index_from_raw, BodyId, GeomId, ObjectId};
+use crate::Actuator;
 use crate::Body;
+use crate::Error;
 use crate::Geom;
+use crate::Joint;
+use crate::Keyframe;
 use crate::Mesh;
+use crate::PhysicsOptions;
+use crate::Sensor;
 use crate::VFS;
 
+use crate::names::NameIndex;
+use crate::vfs::Vfs;
+
 use crate::geom::geom_type_from;
 use crate::helpers::extract_indices;
 use crate::helpers::extract_mesh_attribute;
@@ -18,15 +28,19 @@ use nalgebra::Vector3;
 
 use std::ffi::CStr;
 use std::ffi::CString;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
 
 use arrayvec::ArrayVec;
 
-type Id = u16;
-
 /// A MuJoCo model
 #[derive(Debug)]
 pub struct Model {
     pub(crate) ptr: *mut mjModel,
+    names: NameIndex,
+    /// The number of the load from XML that produced the model, if it was
+    /// compiled from XML. See [`XML_LOADS`].
+    xml_load: Option<u64>,
 }
 
 unsafe impl Send for Model {}
@@ -34,75 +48,157 @@ unsafe impl Sync for Model {}
 
 // Creation, serialization, and deserialization funcs
 impl Model {
-    /// Loads a `Model` from a path to an XML file
+    /// Takes ownership of a model that MuJoCo allocated
     ///
-    /// # Panics
-    /// Panics if the xml is invalid or the file doesn't exist
-    pub fn from_xml(path: impl AsRef<std::path::Path>) -> Result<Self, String> {
+    /// # Safety
+    /// `ptr` must point to a valid `mjModel` that nothing else will free.
+    unsafe fn from_ptr(ptr: *mut mjModel) -> Self {
+        Self {
+            ptr,
+            names: NameIndex::new(&*ptr),
+            xml_load: None,
+        }
+    }
+
+    /// Loads a `Model` from a path to an XML file
+    pub fn from_xml(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
         let path = path.as_ref();
         if !path.is_file() {
-            return Err("File doesn't exist!".to_owned());
+            return Err(Error::FileNotFound(path.to_owned()));
         }
-        let filepath =
-            CString::new(path.to_str().expect("Could not convert `path` to unicode!"))
-                .expect("`path` had an unexpected null byte in its interior!");
+        load_xml(&path_to_cstring(path)?, None)
+    }
 
-        let mut err_buf = Vec::new();
-        // TODO: Would it be safe to just allocate w/o init?
-        err_buf.resize(1000, b'\0'); // Allocate and initialize 1000 null bytes
+    /// Loads a `Model` from an XML file in `vfs`. Any files that the XML
+    /// references are also looked up in `vfs`, falling back to the disk if they
+    /// are not found there.
+    pub fn from_xml_vfs(
+        path: impl AsRef<std::path::Path>,
+        vfs: &Vfs,
+    ) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let filepath = path_to_cstring(path)?;
+        if !vfs.contains_file(path) && !path.is_file() {
+            return Err(Error::FileNotFound(path.to_owned()));
+        }
+        load_xml(&filepath, Some(vfs))
+    }
 
-        let model_ptr = unsafe {
-            mujoco_rs_sys::no_render::mj_loadXML(
-                filepath.as_ptr(),
-                std::ptr::null(),
-                err_buf.as_mut_ptr() as *mut std::os::raw::c_char,
-                err_buf.len() as std::os::raw::c_int,
-            )
-        };
-        from_xml_helper(model_ptr, err_buf)
+    /// Loads a `Model` from an XML string, with any files that it references
+    /// looked up in `vfs`. The XML is only added to `vfs` for the duration of
+    /// the call.
+    pub fn from_xml_str_vfs(
+        xml: impl AsRef<str>,
+        vfs: &mut Vfs,
+    ) -> Result<Self, Error> {
+        let filename = xml_str_filename();
+        vfs.add_file(&filename, xml.as_ref().as_bytes())?;
+        let result = load_xml(&CString::new(filename.as_str()).unwrap(), Some(vfs));
+        vfs.delete_file(&filename);
+        result
     }
 
     /// Loads a `Model` from an XML string
+    pub fn from_xml_str(xml: impl AsRef<str>) -> Result<Self, Error> {
+        Self::from_xml_str_with_assets(xml, std::iter::empty::<(&str, &[u8])>(), None)
+    }
+
+    /// Loads a `Model` from an XML string, along with the meshes, heightfields,
+    /// textures and `<include>` files that it references.
     ///
-    /// # Panics
-    /// Panics if the xml is invalid
-    pub fn from_xml_str(xml: impl AsRef<str>) -> Result<Self, String> {
+    /// `assets` maps filenames to their contents, and is only made visible to
+    /// MuJoCo for the duration of the call. MuJoCo matches assets by filename
+    /// alone, so any directories in the keys are ignored. Assets that are not
+    /// in `assets` are read from disk, relative to `base_dir` if one is given.
+    pub fn from_xml_str_with_assets<K, V>(
+        xml: impl AsRef<str>,
+        assets: impl IntoIterator<Item = (K, V)>,
+        base_dir: Option<&std::path::Path>,
+    ) -> Result<Self, Error>
+    where
+        K: AsRef<str>,
+        V: AsRef<[u8]>,
+    {
         let xml = xml.as_ref();
-        let filename = "from_xml_str";
-        let filename_cstr = CString::new(filename).unwrap();
+        let filename = xml_str_filename();
+        // MuJoCo resolves relative asset paths against the XML's directory
+        let filepath = match base_dir {
+            Some(dir) => dir
+                .join(&filename)
+                .to_str()
+                .ok_or_else(|| Error::NonUtf8Path(dir.to_owned()))?
+                .to_owned(),
+            None => filename.clone(),
+        };
+        let filepath_cstr = CString::new(filepath)?;
         VFS.with(|rcell| {
             let mut vfs = rcell.borrow_mut();
-            vfs.add_file(filename, xml.as_bytes()).unwrap();
+            let mut added = Vec::new();
+            let result = add_assets(&mut vfs, &mut added, assets).and_then(|_| {
+                vfs.add_file(&filename, xml.as_bytes())?;
+                added.push(filename);
+                load_xml(&filepath_cstr, Some(&vfs))
+            });
+            for name in &added {
+                vfs.delete_file(name);
+            }
+            result
+        })
+    }
 
-            let mut err_buf = Vec::new();
-            // TODO: Would it be safe to just allocate w/o init?
-            err_buf.resize(1000, b'\0'); // Allocate and initialize 1000 null bytes
+    /// Loads a `Model` from a zip or tar(.gz) archive on disk. `entry_xml` is the
+    /// path of the MJCF file inside the archive, and every other file in the
+    /// archive is made available to it as an asset.
+    ///
+    /// The archive is unpacked in memory, and MuJoCo matches assets by filename
+    /// alone, so the archive must not contain two files with the same filename.
+    #[cfg(feature = "archive")]
+    pub fn from_archive(
+        path: impl AsRef<std::path::Path>,
+        entry_xml: &str,
+    ) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => Error::FileNotFound(path.to_owned()),
+            _ => Error::Io(format!("{}: {}", path.display(), e)),
+        })?;
+        Self::from_archive_bytes(bytes, entry_xml)
+    }
 
-            let model_ptr = unsafe {
-                mujoco_rs_sys::no_render::mj_loadXML(
-                    filename_cstr.as_ptr(),
-                    &*vfs.vfs,
-                    err_buf.as_mut_ptr() as *mut std::os::raw::c_char,
-                    err_buf.len() as std::os::raw::c_int,
-                )
-            };
-            vfs.delete_file(filename);
-            from_xml_helper(model_ptr, err_buf)
-        })
+    /// Loads a `Model` from the bytes of a zip or tar(.gz) archive. See
+    /// [`Model::from_archive()`].
+    #[cfg(feature = "archive")]
+    pub fn from_archive_bytes(
+        bytes: impl AsRef<[u8]>,
+        entry_xml: &str,
+    ) -> Result<Self, Error> {
+        let entry_xml = entry_xml.trim_start_matches("./");
+        let (xml, assets): (Vec<_>, Vec<_>) =
+            crate::archive::read_archive(bytes.as_ref())?
+                .into_iter()
+                .partition(|entry| entry.path == entry_xml);
+        let xml = xml
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::FileNotFound(entry_xml.into()))?;
+        let xml = String::from_utf8(xml.contents).map_err(|_| {
+            Error::InvalidArchive(format!("{} is not valid UTF-8", entry_xml))
+        })?;
+        let assets = assets
+            .iter()
+            .map(|entry| (entry.path.as_str(), entry.contents.as_slice()));
+        Self::from_xml_str_with_assets(xml, assets, None)
     }
 
     /// Loads a model from a slice of bytes, interpreted as the MJB format
-    ///
-    /// # Panics
-    /// Panics if the bytes are an invalid model
-    pub fn from_bytes(bytes: impl AsRef<[u8]>) -> Self {
+    pub fn from_bytes(bytes: impl AsRef<[u8]>) -> Result<Self, Error> {
         let bytes = bytes.as_ref();
         let filename = "from_bytes";
         let filename_cstr = CString::new(filename).unwrap();
         VFS.with(|rcell| {
             let mut vfs = rcell.borrow_mut();
 
-            vfs.add_file(filename, bytes).unwrap();
+            vfs.add_file(filename, bytes)?;
             let model_ptr = unsafe {
                 mujoco_rs_sys::no_render::mj_loadModel(
                     filename_cstr.as_ptr(),
@@ -110,7 +206,10 @@ impl Model {
                 )
             };
             vfs.delete_file(filename);
-            Self { ptr: model_ptr }
+            if model_ptr.is_null() {
+                return Err(Error::InvalidMjb);
+            }
+            Ok(unsafe { Self::from_ptr(model_ptr) })
         })
     }
 
@@ -130,6 +229,74 @@ impl Model {
         };
         buf
     }
+
+    /// Saves the `Model` as MJCF XML at `path`.
+    ///
+    /// MuJoCo can only do this for the model it most recently compiled from
+    /// XML in this process, so this fails with [`Error::SaveXml`] once any other
+    /// model has been loaded from XML, and for models that were not compiled
+    /// from XML at all, such as ones loaded with [`Model::from_bytes()`] or
+    /// cloned. The real-valued fields of the `Model` (such as sizes, positions,
+    /// and options) are written out, so changes made to them since loading are
+    /// preserved.
+    pub fn save_xml(&self, path: impl AsRef<std::path::Path>) -> Result<(), Error> {
+        let filepath = path_to_cstring(path.as_ref())?;
+
+        // Keeps other threads from loading a model until we are done
+        let loads = lock_xml_loads();
+        if self.xml_load != Some(*loads) {
+            return Err(Error::SaveXml(
+                "only the model most recently compiled from XML can be saved"
+                    .to_owned(),
+            ));
+        }
+
+        let mut err_buf = Vec::new();
+        err_buf.resize(1000, b'\0'); // Allocate and initialize 1000 null bytes
+
+        let success = unsafe {
+            mujoco_rs_sys::no_render::mj_saveLastXML(
+                filepath.as_ptr(),
+                self.ptr,
+                err_buf.as_mut_ptr() as *mut std::os::raw::c_char,
+                err_buf.len() as std::os::raw::c_int,
+            )
+        };
+        if success == 0 {
+            let err_str = crate::helpers::convert_err_buf(err_buf);
+            return Err(Error::SaveXml(err_str));
+        }
+        Ok(())
+    }
+
+    /// Serializes the `Model` into a string of MJCF XML. See
+    /// [`Model::save_xml()`] for the restrictions on which models can be saved.
+    pub fn to_xml_string(&self) -> Result<String, Error> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        // MuJoCo can only write the XML to a file, so we go through a
+        // temporary one that is unique to this call
+        let path = std::env::temp_dir().join(format!(
+            "mujoco-rust-{}-{}.xml",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let result = self.save_xml(&path).and_then(|_| {
+            std::fs::read_to_string(&path).map_err(|e| Error::SaveXml(e.to_string()))
+        });
+        let _ = std::fs::remove_file(&path);
+        result
+    }
+
+    /// Like [`Model::to_xml_string()`], but with `keyframes` added after the
+    /// keyframes that the model already has, such as ones captured with
+    /// [`Simulation::capture_keyframe()`](crate::Simulation::capture_keyframe).
+    /// The same restrictions on which models can be saved apply.
+    pub fn to_xml_string_with_keyframes(
+        &self,
+        keyframes: &[Keyframe],
+    ) -> Result<String, Error> {
+        crate::keyframe::add_keyframes_xml(&self.to_xml_string()?, keyframes)
+    }
 }
 // Accessors
 impl Model {
@@ -138,69 +305,102 @@ impl Model {
         self.ptr
     }
 
-    /// Converts `name` to an id that serves as an offset into the arrays in the
-    /// underlying [`mjModel`]
-    pub fn name_to_id(&self, obj_type: ObjType, name: &str) -> Option<Id> {
-        self.cstr_name_to_id(obj_type, &CString::new(name).unwrap())
+    /// Converts `name` to the id of the object of kind `I` with that name. Ids
+    /// serve as offsets into the arrays in the underlying [`mjModel`].
+    ///
+    /// Names are looked up in an index that is built when the `Model` is
+    /// loaded, so this is cheap enough to call in a control loop.
+    ///
+    /// ```ignore
+    /// let body: Option<BodyId> = model.name_to_id("body1");
+    /// ```
+    pub fn name_to_id<I: ObjectId>(&self, name: &str) -> Option<I> {
+        self.name_to_index(I::OBJ_TYPE, name).map(I::new)
     }
 
-    pub fn cstr_name_to_id(&self, obj_type: ObjType, name: &CStr) -> Option<Id> {
-        let result = unsafe {
-            mujoco_rs_sys::no_render::mj_name2id(
-                self.ptr,
-                obj_type as std::os::raw::c_int,
-                name.as_ptr(),
-            )
-        };
-        if result == -1 {
-            None
-        } else {
-            Some(result as Id)
-        }
+    /// Like [`Model::name_to_id()`], but takes the name as a [`CStr`]
+    pub fn cstr_name_to_id<I: ObjectId>(&self, name: &CStr) -> Option<I> {
+        self.name_to_id(name.to_str().ok()?)
     }
 
-    pub fn id_to_name(&self, obj_type: ObjType, id: Id) -> Option<&str> {
-        let cstr = unsafe {
-            mujoco_rs_sys::no_render::mj_id2name(
-                self.ptr,
-                obj_type as std::os::raw::c_int,
-                id as std::os::raw::c_int,
-            )
-        };
-        #[allow(clippy::cmp_null)]
-        if cstr == std::ptr::null() {
-            return None;
-        }
-        let cstr = unsafe { CStr::from_ptr(cstr) };
-        Some(cstr.to_str().expect("Expected valid unicode for the name!"))
+    /// Gets the name of the object with id `id`, if it exists and has a name
+    pub fn id_to_name<I: ObjectId>(&self, id: I) -> Option<&str> {
+        self.index_to_name(I::OBJ_TYPE, id.index())
     }
 
-    /// Return a list of names used in a model
-    pub fn names(&self) -> Vec<String> {
-        let mj_model = self;
-        let mj_model = unsafe { *mj_model.ptr() };
+    /// Iterates over the ids and names of all the named objects of kind `I`, in
+    /// order of id. Unnamed objects are skipped.
+    ///
+    /// ```ignore
+    /// for (id, name) in model.names_of::<JointId>() { /* ... */ }
+    /// ```
+    pub fn names_of<I: ObjectId>(&self) -> impl Iterator<Item = (I, &str)> + '_ {
+        self.names
+            .iter(I::OBJ_TYPE)
+            .map(|(index, name)| (I::new(index), name))
+    }
 
-        let mut names: Vec<String> = Vec::new();
-        let mut j: isize = 0;
-        for _ in 0..mj_model.nnames {
-            names.push(String::new());
+    /// Converts `name` to an untyped offset into the arrays for objects of
+    /// kind `obj_type`. Prefer [`Model::name_to_id()`] for the kinds of object
+    /// that have an id type.
+    pub fn name_to_index(&self, obj_type: ObjType, name: &str) -> Option<usize> {
+        self.names.index(obj_type, name)
+    }
 
-            loop {
-                let char = unsafe { *mj_model.names.offset(j) };
-                if char == 0 {
-                    j += 1;
-                    break;
-                }
-                let idx = names.len() - 1;
-                if char != 0 {
-                    names[idx] =
-                        format!("{}{}", names[names.len() - 1], (char as u8) as char);
-                }
-                j += 1;
-            }
+    /// Like [`Model::name_to_index()`], but takes the name as a [`CStr`]
+    pub fn cstr_name_to_index(&self, obj_type: ObjType, name: &CStr) -> Option<usize> {
+        self.name_to_index(obj_type, name.to_str().ok()?)
+    }
+
+    /// Gets the name of the object of kind `obj_type` at offset `index`, if it
+    /// exists and has a name
+    pub fn index_to_name(&self, obj_type: ObjType, index: usize) -> Option<&str> {
+        self.names.name(obj_type, index)
+    }
+
+    /// Iterates over the offsets and names of all the named objects of kind
+    /// `obj_type`. See [`Model::names_of()`].
+    pub fn indices_and_names(
+        &self,
+        obj_type: ObjType,
+    ) -> impl Iterator<Item = (usize, &str)> + '_ {
+        self.names.iter(obj_type)
+    }
+
+    /// Gets the physics options of the model, such as the timestep and gravity
+    pub fn options(&self) -> &PhysicsOptions {
+        PhysicsOptions::from_raw(unsafe { &(*self.ptr).opt })
+    }
+
+    /// Gets the physics options of the model for modification. Changes take
+    /// effect from the next step of any simulation using the model.
+    pub fn options_mut(&mut self) -> &mut PhysicsOptions {
+        PhysicsOptions::from_raw_mut(unsafe { &mut (*self.ptr).opt })
+    }
+
+    /// Returns the number of objects of kind `obj_type` in the model
+    pub fn count(&self, obj_type: ObjType) -> usize {
+        match obj_type {
+            ObjType::DOF => self.nv(),
+            obj_type => self.names.len(obj_type),
         }
+    }
 
-        names
+    /// Returns every non-empty name in the model, including the model's own
+    /// name, in the order that MuJoCo stores them
+    pub fn names(&self) -> Vec<String> {
+        let m = unsafe { &*self.ptr };
+        let nnames = m.nnames.max(0) as usize;
+        if nnames == 0 {
+            return Vec::new();
+        }
+        // `names` holds `nnames` bytes of NUL-terminated names
+        let buf = unsafe { std::slice::from_raw_parts(m.names as *const u8, nnames) };
+        buf.split(|&c| c == 0)
+            .filter(|name| !name.is_empty())
+            .filter_map(|name| std::str::from_utf8(name).ok())
+            .map(str::to_owned)
+            .collect()
     }
 
     /// Returns a number of geoms in the model
@@ -429,9 +629,9 @@ impl Model {
 
             let geom_body = unsafe {
                 Geom {
-                    id: i as i32,
+                    id: GeomId::new(i),
                     geom_type: geom_type_from(*mj_model.geom_type.add(i) as usize),
-                    body_id: *mj_model.geom_bodyid.add(i),
+                    body_id: BodyId::new(*mj_model.geom_bodyid.add(i) as usize),
                     geom_group: *mj_model.geom_group.add(i),
                     geom_contype: *mj_model.geom_contype.add(i),
                     pos: Vector3::from(pos_array),
@@ -453,6 +653,50 @@ impl Model {
         geoms
     }
 
+    /// Get actuators of the model
+    pub fn actuators(&self) -> Vec<Actuator> {
+        let m = unsafe { &*self.ptr };
+        (0..self.nu())
+            .map(|i| {
+                let name = self.index_to_name(ObjType::ACTUATOR, i).unwrap_or("");
+                unsafe { Actuator::from_model(m, i, name) }
+            })
+            .collect()
+    }
+
+    /// Get joints of the model
+    pub fn joints(&self) -> Vec<Joint> {
+        let m = unsafe { &*self.ptr };
+        (0..self.count(ObjType::JOINT))
+            .map(|i| {
+                let name = self.index_to_name(ObjType::JOINT, i).unwrap_or("");
+                unsafe { Joint::from_model(m, i, name) }
+            })
+            .collect()
+    }
+
+    /// Get sensors of the model
+    pub fn sensors(&self) -> Vec<Sensor> {
+        let m = unsafe { &*self.ptr };
+        (0..self.count(ObjType::SENSOR))
+            .map(|i| {
+                let name = self.index_to_name(ObjType::SENSOR, i).unwrap_or("");
+                unsafe { Sensor::from_model(m, i, name) }
+            })
+            .collect()
+    }
+
+    /// Gets the keyframes of the model, in order of id
+    pub fn keyframes(&self) -> Vec<Keyframe> {
+        let m = unsafe { &*self.ptr };
+        (0..self.count(ObjType::KEY))
+            .map(|i| {
+                let name = self.index_to_name(ObjType::KEY, i).unwrap_or("");
+                unsafe { Keyframe::from_model(m, i, name) }
+            })
+            .collect()
+    }
+
     /// Get bodies of the model
     pub fn bodies(&self) -> Vec<Body> {
         let mj_model = self;
@@ -504,10 +748,11 @@ impl Model {
 
             let geom_body = unsafe {
                 Body {
-                    id: i as i32,
-                    parent_id: *mj_model.body_parentid.add(i),
-                    geom_n: *mj_model.body_geomnum.add(i),
-                    geom_addr: *mj_model.body_geomadr.add(i),
+                    id: BodyId::new(i),
+                    parent_id: BodyId::new(*mj_model.body_parentid.add(i) as usize),
+                    geom_n: *mj_model.body_geomnum.add(i) as usize,
+                    geom_addr: index_from_raw(*mj_model.body_geomadr.add(i))
+                        .map(GeomId::new),
                     pos: Vector3::from(pos_array),
                     quat: Quaternion::new(
                         quat_array[0],
@@ -534,39 +779,112 @@ impl Clone for Model {
         let ptr = unsafe {
             mujoco_rs_sys::no_render::mj_copyModel(std::ptr::null_mut(), self.ptr)
         };
-        Self { ptr }
+        // The copy was not compiled from XML itself, so it can't be saved as XML
+        Self {
+            ptr,
+            names: self.names.clone(),
+            xml_load: None,
+        }
     }
 }
 
-/// Helper function for loading a model from xml
-fn from_xml_helper(model_ptr: *mut mjModel, err_buf: Vec<u8>) -> Result<Model, String> {
-    debug_assert_ne!(err_buf.len(), 0);
-    let err_str = crate::helpers::convert_err_buf(err_buf);
-
-    if !err_str.is_empty() {
-        return Err(err_str);
+/// Helper function for adding assets to a [`Vfs`], recording each filename in
+/// `added` so that the caller can remove them again
+fn add_assets<K, V>(
+    vfs: &mut Vfs,
+    added: &mut Vec<String>,
+    assets: impl IntoIterator<Item = (K, V)>,
+) -> Result<(), Error>
+where
+    K: AsRef<str>,
+    V: AsRef<[u8]>,
+{
+    for (name, contents) in assets {
+        let name = name.as_ref();
+        vfs.add_file(name, contents.as_ref())?;
+        added.push(name.to_owned());
     }
+    Ok(())
+}
+
+/// Helper function for naming an XML string in a [`Vfs`], as MuJoCo can only
+/// load XML from a file. The name is unique to the call, so that it can't
+/// collide with an asset or `<include>` of the caller's.
+fn xml_str_filename() -> String {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    format!(
+        "mujoco-rust-{}-{}.xml",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Helper function for converting a path into a form that MuJoCo accepts
+fn path_to_cstring(path: &std::path::Path) -> Result<CString, Error> {
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| Error::NonUtf8Path(path.to_owned()))?;
+    Ok(CString::new(path_str)?)
+}
+
+/// The number of times a model has been loaded from XML in this process.
+/// MuJoCo keeps the last model that it compiled from XML in a global for
+/// `mj_saveLastXML`, so the lock is held across every load from XML and every
+/// save, and a [`Model`] can only be saved while the count is the one it was
+/// loaded with.
+static XML_LOADS: Mutex<u64> = Mutex::new(0);
+
+fn lock_xml_loads() -> MutexGuard<'static, u64> {
+    // The count stays valid even if a thread panicked while holding the lock
+    XML_LOADS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Helper function for loading a model from xml
+fn load_xml(filepath: &CStr, vfs: Option<&Vfs>) -> Result<Model, Error> {
+    let mut err_buf = Vec::new();
+    // TODO: Would it be safe to just allocate w/o init?
+    err_buf.resize(1000, b'\0'); // Allocate and initialize 1000 null bytes
+
+    // A failed load may also replace the model that MuJoCo would save, so every
+    // attempt counts
+    let mut loads = lock_xml_loads();
+    *loads += 1;
+    let vfs_ptr = vfs.map_or(std::ptr::null(), |vfs| &*vfs.vfs as *const _);
+    let model_ptr = unsafe {
+        mujoco_rs_sys::no_render::mj_loadXML(
+            filepath.as_ptr(),
+            vfs_ptr,
+            err_buf.as_mut_ptr() as *mut std::os::raw::c_char,
+            err_buf.len() as std::os::raw::c_int,
+        )
+    };
+    // A non-null model may still come with a warning in `err_buf`, which we ignore
     if model_ptr.is_null() {
-        unreachable!(
-            "It shouldn't be possible to get a null pointer from mujoco \
-                without an error message!"
-        );
+        let err_str = crate::helpers::convert_err_buf(err_buf);
+        return Err(Error::from_xml_message(err_str));
     }
-    Ok(Model { ptr: model_ptr })
+    let mut model = unsafe { Model::from_ptr(model_ptr) };
+    model.xml_load = Some(*loads);
+    Ok(model)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tests::{SIMPLE_XML, SIMPLE_XML_PATH};
+    use crate::id::{CameraId, JointId, LightId, SiteId};
+    use crate::tests::{PKG_ROOT, SIMPLE_XML, SIMPLE_XML_PATH};
+    use crate::{
+        BiasType, DynType, GainType, JointType, SensorDataType, SensorObject,
+        SensorType, Stage, Transmission,
+    };
 
     fn check_expected_ids(m: &Model) {
-        assert_eq!(m.name_to_id(ObjType::BODY, "world").unwrap(), 0);
-        assert_eq!(m.name_to_id(ObjType::LIGHT, "light0").unwrap(), 0);
-        assert_eq!(m.name_to_id(ObjType::GEOM, "geom0").unwrap(), 0);
-        assert_eq!(m.name_to_id(ObjType::BODY, "body1").unwrap(), 1);
-        assert_eq!(m.name_to_id(ObjType::JOINT, "joint0").unwrap(), 0);
-        assert_eq!(m.name_to_id(ObjType::GEOM, "geom1").unwrap(), 1);
+        assert_eq!(m.name_to_id("world"), Some(BodyId::WORLD));
+        assert_eq!(m.name_to_id("light0"), Some(LightId::new(0)));
+        assert_eq!(m.name_to_id("geom0"), Some(GeomId::new(0)));
+        assert_eq!(m.name_to_id("body1"), Some(BodyId::new(1)));
+        assert_eq!(m.name_to_id("joint0"), Some(JointId::new(0)));
+        assert_eq!(m.name_to_id("geom1"), Some(GeomId::new(1)));
         assert_eq!(unsafe { *(*m.ptr()).geom_size.offset(3) }, 0.1);
     }
 
@@ -586,14 +904,235 @@ mod tests {
         check_expected_ids(&model_xml);
     }
 
+    #[test]
+    fn from_xml_str_with_assets() {
+        let xml = r#"<mujoco>
+    <asset>
+        <mesh name="tetra" file="meshes/tetra.obj"/>
+    </asset>
+    <worldbody>
+        <include file="light.xml"/>
+        <body name="body1">
+            <geom name="geom0" type="mesh" mesh="tetra"/>
+        </body>
+    </worldbody>
+</mujoco>"#;
+        let light = r#"<mujoco><light name="light0"/></mujoco>"#;
+        let tetra = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\n\
+                     f 1 3 2\nf 1 2 4\nf 1 4 3\nf 2 3 4\n";
+        let assets = [
+            ("meshes/tetra.obj", tetra.as_bytes()),
+            ("light.xml", light.as_bytes()),
+        ];
+
+        let m =
+            Model::from_xml_str_with_assets(xml, assets.iter().copied(), None).unwrap();
+        assert_eq!(m.nmesh(), 1);
+        assert_eq!(m.name_to_id("light0"), Some(LightId::new(0)));
+        assert_eq!(m.name_to_id("geom0"), Some(GeomId::new(0)));
+
+        // The assets should not outlive the call
+        let missing = Model::from_xml_str(xml).unwrap_err();
+        assert!(matches!(missing, Error::Xml { .. }));
+    }
+
+    #[test]
+    fn from_xml_str_with_assets_base_dir() {
+        let xml = r#"<mujoco><include file="simple.xml"/></mujoco>"#;
+        let base_dir = SIMPLE_XML_PATH.parent().unwrap();
+        let no_assets = std::iter::empty::<(&str, &[u8])>();
+
+        let m =
+            Model::from_xml_str_with_assets(xml, no_assets, Some(base_dir)).unwrap();
+        check_expected_ids(&m);
+    }
+
+    #[test]
+    fn from_xml_vfs() {
+        let mut vfs = Vfs::new();
+        vfs.add_file("staged.xml", SIMPLE_XML.as_bytes()).unwrap();
+
+        let m = Model::from_xml_vfs("staged.xml", &vfs).unwrap();
+        check_expected_ids(&m);
+
+        let missing = Model::from_xml_vfs("missing.xml", &vfs);
+        assert_eq!(
+            missing.unwrap_err(),
+            Error::FileNotFound("missing.xml".into())
+        );
+    }
+
+    #[test]
+    fn from_xml_str_vfs() {
+        let mut vfs = Vfs::new();
+        vfs.add_file("simple.xml", SIMPLE_XML.as_bytes()).unwrap();
+
+        let xml = r#"<mujoco><include file="simple.xml"/></mujoco>"#;
+        let m = Model::from_xml_str_vfs(xml, &mut vfs).unwrap();
+        check_expected_ids(&m);
+        assert!(vfs.get_file("simple.xml").is_some());
+
+        // The XML string doesn't take up any name that the caller could use
+        let mut vfs = Vfs::new();
+        vfs.add_file("from_xml_str", SIMPLE_XML.as_bytes()).unwrap();
+        let xml = r#"<mujoco><include file="from_xml_str"/></mujoco>"#;
+        let m = Model::from_xml_str_vfs(xml, &mut vfs).unwrap();
+        check_expected_ids(&m);
+        let m = Model::from_xml_str_with_assets(
+            xml,
+            [("from_xml_str", SIMPLE_XML.as_bytes())],
+            None,
+        )
+        .unwrap();
+        check_expected_ids(&m);
+    }
+
+    #[cfg(feature = "archive")]
+    #[test]
+    fn from_archive_bytes() {
+        use crate::archive::tests::{make_tar_gz, make_zip};
+
+        let xml = r#"<mujoco><include file="inc/simple.xml"/></mujoco>"#;
+        let files: &[(&str, &[u8])] = &[
+            ("robot/robot.xml", xml.as_bytes()),
+            ("robot/inc/simple.xml", SIMPLE_XML.as_bytes()),
+        ];
+
+        let m = Model::from_archive_bytes(make_zip(files), "robot/robot.xml").unwrap();
+        check_expected_ids(&m);
+        let m =
+            Model::from_archive_bytes(make_tar_gz(files), "robot/robot.xml").unwrap();
+        check_expected_ids(&m);
+
+        let missing = Model::from_archive_bytes(make_zip(files), "robot.xml");
+        assert_eq!(
+            missing.unwrap_err(),
+            Error::FileNotFound("robot.xml".into())
+        );
+    }
+
     #[test]
     fn from_bytes() {
         let model_xml = Model::from_xml(&*SIMPLE_XML_PATH).unwrap(); // _str
         let model_xml_bytes = model_xml.to_vec();
-        let model_from_bytes = Model::from_bytes(&model_xml_bytes);
+        let model_from_bytes = Model::from_bytes(&model_xml_bytes).unwrap();
         assert_eq!(model_from_bytes.to_vec(), model_xml_bytes);
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_descriptors() {
+        let m = Model::from_xml_str(*SIMPLE_XML).unwrap();
+        let geoms = serde_json::to_string(&m.geoms()).unwrap();
+        let decoded: Vec<Geom> = serde_json::from_str(&geoms).unwrap();
+        assert_eq!(decoded[0].geom_type, crate::GeomType::PLANE);
+        assert_eq!(decoded[1].body_id, BodyId::new(1));
+        assert_eq!(serde_json::to_string(&decoded).unwrap(), geoms);
+
+        let bodies = serde_json::to_string(&m.bodies()).unwrap();
+        let decoded: Vec<Body> = serde_json::from_str(&bodies).unwrap();
+        assert_eq!(decoded[1].name, "body1");
+        assert_eq!(serde_json::to_string(&decoded).unwrap(), bodies);
+
+        let xml = r#"<mujoco>
+    <worldbody>
+        <body name="arm">
+            <joint name="hinge" type="hinge"/>
+            <geom type="capsule" size=".05" fromto="0 0 0 .5 0 0"/>
+            <site name="tip" pos=".5 0 0"/>
+        </body>
+    </worldbody>
+    <actuator>
+        <motor name="motor" joint="hinge"/>
+        <general site="tip" dyntype="filter" biastype="affine"/>
+    </actuator>
+    <sensor>
+        <jointpos name="angle" joint="hinge"/>
+        <framequat objtype="site" objname="tip" reftype="body" refname="arm"/>
+    </sensor>
+</mujoco>"#;
+        let m = Model::from_xml_str(xml).unwrap();
+        let joints = serde_json::to_string(&m.joints()).unwrap();
+        let decoded: Vec<Joint> = serde_json::from_str(&joints).unwrap();
+        assert_eq!(decoded[0].joint_type, JointType::HINGE);
+        assert_eq!(serde_json::to_string(&decoded).unwrap(), joints);
+
+        let actuators = serde_json::to_string(&m.actuators()).unwrap();
+        let decoded: Vec<Actuator> = serde_json::from_str(&actuators).unwrap();
+        assert_eq!(
+            decoded[0].transmission,
+            Transmission::Joint(JointId::new(0))
+        );
+        assert_eq!(decoded[1].dyn_type, DynType::FILTER);
+        assert_eq!(decoded[1].gain_type, GainType::FIXED);
+        assert_eq!(decoded[1].bias_type, BiasType::AFFINE);
+        assert_eq!(serde_json::to_string(&decoded).unwrap(), actuators);
+
+        let sensors = serde_json::to_string(&m.sensors()).unwrap();
+        let decoded: Vec<Sensor> = serde_json::from_str(&sensors).unwrap();
+        assert_eq!(decoded[0].sensor_type, SensorType::JOINTPOS);
+        assert_eq!(decoded[0].stage, Stage::POS);
+        assert_eq!(decoded[1].data_type, SensorDataType::QUATERNION);
+        assert_eq!(
+            decoded[1].reference,
+            Some(SensorObject::Body(BodyId::new(1)))
+        );
+        assert_eq!(serde_json::to_string(&decoded).unwrap(), sensors);
+
+        // Kinds of object without an id type go through the bindings' names
+        let other = SensorObject::Other(ObjType::PLUGIN, 2);
+        let json = serde_json::to_string(&other).unwrap();
+        assert_eq!(json, r#"{"Other":["PLUGIN",2]}"#);
+        assert_eq!(serde_json::from_str::<SensorObject>(&json).unwrap(), other);
+    }
+
+    #[test]
+    #[cfg(feature = "archive")]
+    fn from_archive_unreadable() {
+        let path = PKG_ROOT.join("tests").join("res").join("missing.zip");
+        let result = Model::from_archive(&path, "robot.xml");
+        assert_eq!(result.unwrap_err(), Error::FileNotFound(path));
+
+        let dir = PKG_ROOT.join("tests").join("res");
+        match Model::from_archive(dir, "robot.xml").unwrap_err() {
+            Error::Io(_) => {}
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn from_bytes_invalid() {
+        let result = Model::from_bytes(b"not a model");
+        assert_eq!(result.unwrap_err(), Error::InvalidMjb);
+    }
+
+    #[test]
+    fn from_xml_missing_file() {
+        let path = PKG_ROOT.join("tests").join("res").join("missing.xml");
+        let result = Model::from_xml(&path);
+        assert_eq!(result.unwrap_err(), Error::FileNotFound(path));
+    }
+
+    #[test]
+    fn from_xml_str_invalid() {
+        let xml = r#"<mujoco>
+    <worldbody>
+        <body name="body0"/>
+        <body name="body0"/>
+    </worldbody>
+</mujoco>"#;
+        match Model::from_xml_str(xml).unwrap_err() {
+            Error::Xml { line, .. } => assert_eq!(line, Some(4)),
+            e => panic!("Unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn from_xml_str_malformed() {
+        let result = Model::from_xml_str("<mujoco><worldbody></mujoco>");
+        assert!(matches!(result.unwrap_err(), Error::Xml { .. }));
+    }
+
     #[test]
     fn serialize() {
         let m = Model::from_xml(&*SIMPLE_XML_PATH).unwrap();
@@ -611,10 +1150,15 @@ mod tests {
         check_expected_ids(&m);
 
         // Check that non-existent names give `None`
-        assert_eq!(m.name_to_id(ObjType::BODY, "asdf"), None);
-        assert_eq!(m.name_to_id(ObjType::BODY, "body0"), None);
-        assert_eq!(m.name_to_id(ObjType::GEOM, "geom2"), None);
-        assert_eq!(m.name_to_id(ObjType::CAMERA, "cam"), None);
+        assert_eq!(m.name_to_id::<BodyId>("asdf"), None);
+        assert_eq!(m.name_to_id::<BodyId>("body0"), None);
+        assert_eq!(m.name_to_id::<GeomId>("geom2"), None);
+        assert_eq!(m.name_to_id::<CameraId>("cam"), None);
+        assert_eq!(m.name_to_id::<BodyId>("nul\0"), None);
+
+        // Ids of one kind don't find objects of another kind
+        assert_eq!(m.name_to_id::<JointId>("body1"), None);
+        assert_eq!(m.name_to_index(ObjType::BODY, "body1"), Some(1));
     }
 
     #[test]
@@ -622,18 +1166,19 @@ mod tests {
         let m = Model::from_xml(&*SIMPLE_XML_PATH).unwrap();
 
         // Check expected values
-        assert_eq!(m.id_to_name(ObjType::BODY, 0).unwrap(), "world");
-        assert_eq!(m.id_to_name(ObjType::LIGHT, 0).unwrap(), "light0");
-        assert_eq!(m.id_to_name(ObjType::GEOM, 0).unwrap(), "geom0");
-        assert_eq!(m.id_to_name(ObjType::BODY, 1).unwrap(), "body1");
-        assert_eq!(m.id_to_name(ObjType::JOINT, 0).unwrap(), "joint0");
-        assert_eq!(m.id_to_name(ObjType::GEOM, 1).unwrap(), "geom1");
+        assert_eq!(m.id_to_name(BodyId::WORLD).unwrap(), "world");
+        assert_eq!(m.id_to_name(LightId::new(0)).unwrap(), "light0");
+        assert_eq!(m.id_to_name(GeomId::new(0)).unwrap(), "geom0");
+        assert_eq!(m.id_to_name(BodyId::new(1)).unwrap(), "body1");
+        assert_eq!(m.id_to_name(JointId::new(0)).unwrap(), "joint0");
+        assert_eq!(m.id_to_name(GeomId::new(1)).unwrap(), "geom1");
 
         // Check that non-existent ids give `None`
-        assert_eq!(m.id_to_name(ObjType::BODY, 2), None);
-        assert_eq!(m.id_to_name(ObjType::LIGHT, 1), None);
-        assert_eq!(m.id_to_name(ObjType::GEOM, 2), None);
-        assert_eq!(m.id_to_name(ObjType::CAMERA, 0), None);
+        assert_eq!(m.id_to_name(BodyId::new(2)), None);
+        assert_eq!(m.id_to_name(LightId::new(1)), None);
+        assert_eq!(m.id_to_name(GeomId::new(2)), None);
+        assert_eq!(m.id_to_name(CameraId::new(0)), None);
+        assert_eq!(m.id_to_name(BodyId::new(usize::MAX)), None);
     }
 
     #[test]
@@ -648,8 +1193,165 @@ mod tests {
     fn bodies() {
         let model = Model::from_xml(&*SIMPLE_XML_PATH).unwrap();
         let bodies = model.bodies();
+        let geoms = model.geoms();
 
         assert!(bodies.len() == 2);
+        assert_eq!(bodies[1].id, BodyId::new(1));
+        assert_eq!(bodies[1].parent_id, BodyId::WORLD);
+        assert_eq!(bodies[1].geom_addr, Some(GeomId::new(1)));
+        assert_eq!(bodies[1].geoms(&geoms)[0].body_id, bodies[1].id);
+    }
+
+    #[test]
+    fn actuators() {
+        let xml = r#"<mujoco>
+    <worldbody>
+        <body name="arm">
+            <joint name="hinge" type="hinge" range="-1 1"/>
+            <geom type="capsule" size=".05" fromto="0 0 0 .5 0 0"/>
+            <site name="tip" pos=".5 0 0"/>
+        </body>
+    </worldbody>
+    <actuator>
+        <motor name="motor" joint="hinge" gear="2" ctrlrange="-1 1"/>
+        <position name="servo" joint="hinge" kp="10" forcerange="-5 5"/>
+        <general site="tip" gear="0 0 1 0 0 0" dyntype="filter" dynprm=".1"/>
+    </actuator>
+</mujoco>"#;
+        let m = Model::from_xml_str(xml).unwrap();
+        let actuators = m.actuators();
+        assert_eq!(actuators.len(), 3);
+        let hinge = m.name_to_id("hinge").unwrap();
+        let tip = m.name_to_id("tip").unwrap();
+
+        let motor = &actuators[0];
+        assert_eq!(motor.name, "motor");
+        assert_eq!(motor.transmission, Transmission::Joint(hinge));
+        assert_eq!(motor.gear[0], 2.0);
+        assert_eq!(motor.ctrl_range, Some([-1.0, 1.0]));
+        assert_eq!(motor.force_range, None);
+        assert_eq!(motor.gain_type, GainType::FIXED);
+        assert_eq!(motor.bias_type, BiasType::NONE);
+
+        let servo = &actuators[1];
+        assert_eq!(servo.force_range, Some([-5.0, 5.0]));
+        assert_eq!(servo.gain_prm[0], 10.0);
+        assert_eq!(servo.bias_type, BiasType::AFFINE);
+        assert_eq!(servo.bias_prm[1], -10.0);
+
+        let general = &actuators[2];
+        assert_eq!(general.name, "");
+        assert_eq!(
+            general.transmission,
+            Transmission::Site {
+                site: tip,
+                refsite: None
+            }
+        );
+        assert_eq!(general.dyn_type, DynType::FILTER);
+        assert_eq!(general.act_addr, Some(0));
+        assert_eq!(general.act_num, 1);
+        assert_eq!(motor.act_addr, None);
+
+        // A missing target isn't mistaken for the object with id 0
+        unsafe { *(*m.ptr()).actuator_trnid = -1 };
+        assert_eq!(m.actuators()[0].transmission, Transmission::Undefined);
+    }
+
+    #[test]
+    fn joints() {
+        let xml = r#"<mujoco>
+    <worldbody>
+        <body name="base">
+            <freejoint name="root"/>
+            <geom type="sphere" size=".1"/>
+            <body name="arm">
+                <joint name="shoulder" type="ball" damping="2"/>
+                <joint name="elbow" type="hinge" axis="0 1 0" range="-1 1"
+                       stiffness="3" armature=".1"/>
+                <geom type="capsule" size=".05" fromto="0 0 0 .5 0 0"/>
+            </body>
+        </body>
+    </worldbody>
+</mujoco>"#;
+        let m = Model::from_xml_str(xml).unwrap();
+        let joints = m.joints();
+        assert_eq!(joints.len(), 3);
+
+        let root = &joints[0];
+        assert_eq!(root.name, "root");
+        assert_eq!(root.joint_type, JointType::FREE);
+        assert_eq!(root.body_id, m.name_to_id("base").unwrap());
+        assert_eq!((root.qpos_addr, root.dof_addr), (0, 0));
+        assert_eq!((root.nq(), root.nv()), (7, 6));
+
+        let shoulder = &joints[1];
+        assert_eq!(shoulder.joint_type, JointType::BALL);
+        assert_eq!((shoulder.qpos_addr, shoulder.dof_addr), (7, 6));
+        assert_eq!(shoulder.damping, 2.0);
+        assert!(!shoulder.limited);
+
+        let elbow = &joints[2];
+        assert_eq!((elbow.qpos_addr, elbow.dof_addr), (11, 9));
+        assert!(elbow.limited);
+        assert_eq!(elbow.range, [-1.0, 1.0]);
+        assert_eq!(elbow.axis, Vector3::y());
+        assert_eq!(elbow.stiffness, 3.0);
+        assert_eq!(elbow.armature, 0.1);
+    }
+
+    #[test]
+    fn sensors() {
+        let xml = r#"<mujoco>
+    <worldbody>
+        <body name="arm">
+            <joint name="hinge" type="hinge"/>
+            <geom type="capsule" size=".05" fromto="0 0 0 .5 0 0"/>
+            <site name="tip" pos=".5 0 0"/>
+        </body>
+    </worldbody>
+    <sensor>
+        <jointpos name="angle" joint="hinge" noise=".01"/>
+        <framequat name="orientation" objtype="site" objname="tip"
+                   reftype="body" refname="arm"/>
+        <accelerometer site="tip" cutoff="50"/>
+        <clock/>
+    </sensor>
+</mujoco>"#;
+        let m = Model::from_xml_str(xml).unwrap();
+        let sensors = m.sensors();
+        assert_eq!(sensors.len(), 4);
+
+        let angle = &sensors[0];
+        assert_eq!(angle.name, "angle");
+        assert_eq!(angle.sensor_type, SensorType::JOINTPOS);
+        assert_eq!(angle.object, Some(SensorObject::Joint(JointId::new(0))));
+        assert_eq!(angle.reference, None);
+        assert_eq!(angle.stage, Stage::POS);
+        assert_eq!((angle.addr, angle.dim), (0, 1));
+        assert_eq!(angle.noise, 0.01);
+
+        let orientation = &sensors[1];
+        assert_eq!(orientation.data_type, SensorDataType::QUATERNION);
+        assert_eq!(orientation.object, Some(SensorObject::Site(SiteId::new(0))));
+        assert_eq!(
+            orientation.reference,
+            Some(SensorObject::Body(BodyId::new(1)))
+        );
+        assert_eq!((orientation.addr, orientation.dim), (1, 4));
+
+        let accelerometer = &sensors[2];
+        assert_eq!(accelerometer.name, "");
+        assert_eq!(accelerometer.stage, Stage::ACC);
+        assert_eq!(accelerometer.cutoff, 50.0);
+        assert_eq!(
+            accelerometer.object,
+            Some(SensorObject::Site(SiteId::new(0)))
+        );
+        assert_eq!((accelerometer.addr, accelerometer.dim), (5, 3));
+
+        assert_eq!(sensors[3].sensor_type, SensorType::CLOCK);
+        assert_eq!(sensors[3].object, None);
     }
 
     #[test]
@@ -666,5 +1368,49 @@ mod tests {
         let names = model.names();
 
         assert!(!names.is_empty());
+        for name in &["world", "light0", "geom0", "body1", "joint0", "geom1"] {
+            assert!(
+                names.iter().any(|n| n == name),
+                "{} missing from {:?}",
+                name,
+                names
+            );
+        }
+        assert!(!names.iter().any(String::is_empty));
+    }
+
+    #[test]
+    fn names_of() {
+        let m = Model::from_xml_str(
+            r#"<mujoco>
+    <worldbody>
+        <geom name="floor" type="plane" size="1 1 0.1"/>
+        <body>
+            <joint/>
+            <geom type="sphere" size=".1"/>
+            <geom name="ball" type="sphere" size=".1"/>
+        </body>
+    </worldbody>
+</mujoco>"#,
+        )
+        .unwrap();
+
+        let geoms: Vec<(GeomId, &str)> = m.names_of().collect();
+        assert_eq!(geoms, [(GeomId::new(0), "floor"), (GeomId::new(2), "ball")]);
+        assert_eq!(m.names_of::<JointId>().count(), 0);
+
+        // Unnamed objects have no name, and the empty name finds nothing
+        assert_eq!(m.id_to_name(BodyId::new(1)), None);
+        assert_eq!(m.id_to_name(GeomId::new(1)), None);
+        assert_eq!(m.name_to_id::<GeomId>(""), None);
+
+        // Inertial frames share the names of their bodies
+        assert_eq!(m.name_to_index(ObjType::XBODY, "world"), Some(0));
+        assert_eq!(m.index_to_name(ObjType::DOF, 0), None);
+        let ball = CString::new("ball").unwrap();
+        assert_eq!(m.cstr_name_to_index(ObjType::GEOM, &ball), Some(2));
+
+        let cloned = m.clone();
+        assert_eq!(cloned.name_to_id("ball"), Some(GeomId::new(2)));
     }
 }
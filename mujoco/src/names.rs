@@ -0,0 +1,167 @@
+//! An index of the names in a [`Model`](crate::Model), built once so that
+//! lookups don't have to go through MuJoCo's string comparisons or allocate.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::ffi::CStr;
+use std::os::raw::c_int;
+
+use mujoco_rs_sys::no_render::mjModel;
+
+use crate::re_exports::ObjType;
+
+/// The names of the objects of one kind
+#[derive(Debug, Clone, Default)]
+struct NameTable {
+    /// The name of each object, or `None` if it is unnamed
+    names: Vec<Option<Box<str>>>,
+    ids: HashMap<Box<str>, usize>,
+}
+
+impl NameTable {
+    /// Reads the names of `n` objects, whose offsets into `m.names` start at
+    /// `adr`.
+    ///
+    /// # Safety
+    /// `adr` must point to `n` valid offsets into the names of `m`.
+    unsafe fn new(m: &mjModel, n: c_int, adr: *const c_int) -> Self {
+        let n = n.max(0) as usize;
+        if n == 0 || adr.is_null() {
+            return Self::default();
+        }
+        let mut table = Self {
+            names: Vec::with_capacity(n),
+            ids: HashMap::with_capacity(n),
+        };
+        for (id, &offset) in std::slice::from_raw_parts(adr, n).iter().enumerate() {
+            let name = CStr::from_ptr(m.names.add(offset as usize)).to_string_lossy();
+            if name.is_empty() {
+                table.names.push(None);
+                continue;
+            }
+            let name: Box<str> = name.into();
+            // MuJoCo resolves a name to the first object that has it
+            table.ids.entry(name.clone()).or_insert(id);
+            table.names.push(Some(name));
+        }
+        table
+    }
+}
+
+/// The names of every kind of object in a model
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NameIndex {
+    /// One table per [`ObjType`], indexed by its discriminant
+    tables: Vec<NameTable>,
+}
+
+impl NameIndex {
+    /// Builds the index for `m`
+    ///
+    /// # Safety
+    /// `m` must be a valid, fully allocated model.
+    pub(crate) unsafe fn new(m: &mjModel) -> Self {
+        let table = |obj_type: ObjType| -> NameTable {
+            let (n, adr) = match obj_type {
+                ObjType::BODY => (m.nbody, m.name_bodyadr),
+                ObjType::JOINT => (m.njnt, m.name_jntadr),
+                ObjType::GEOM => (m.ngeom, m.name_geomadr),
+                ObjType::SITE => (m.nsite, m.name_siteadr),
+                ObjType::CAMERA => (m.ncam, m.name_camadr),
+                ObjType::LIGHT => (m.nlight, m.name_lightadr),
+                ObjType::MESH => (m.nmesh, m.name_meshadr),
+                ObjType::SKIN => (m.nskin, m.name_skinadr),
+                ObjType::HFIELD => (m.nhfield, m.name_hfieldadr),
+                ObjType::TEXTURE => (m.ntex, m.name_texadr),
+                ObjType::MATERIAL => (m.nmat, m.name_matadr),
+                ObjType::PAIR => (m.npair, m.name_pairadr),
+                ObjType::EXCLUDE => (m.nexclude, m.name_excludeadr),
+                ObjType::EQUALITY => (m.neq, m.name_eqadr),
+                ObjType::TENDON => (m.ntendon, m.name_tendonadr),
+                ObjType::ACTUATOR => (m.nu, m.name_actuatoradr),
+                ObjType::SENSOR => (m.nsensor, m.name_sensoradr),
+                ObjType::NUMERIC => (m.nnumeric, m.name_numericadr),
+                ObjType::TEXT => (m.ntext, m.name_textadr),
+                ObjType::TUPLE => (m.ntuple, m.name_tupleadr),
+                ObjType::KEY => (m.nkey, m.name_keyadr),
+                ObjType::PLUGIN => (m.nplugin, m.name_pluginadr),
+                _ => return NameTable::default(),
+            };
+            NameTable::new(m, n, adr)
+        };
+        let tables = OBJ_TYPES.iter().map(|&obj_type| table(obj_type)).collect();
+        Self { tables }
+    }
+
+    fn table(&self, obj_type: ObjType) -> Option<&NameTable> {
+        // Bodies and their inertial frames share names
+        let obj_type = match obj_type {
+            ObjType::XBODY => ObjType::BODY,
+            obj_type => obj_type,
+        };
+        self.tables.get(obj_type as usize)
+    }
+
+    /// The offset of the object of kind `obj_type` named `name`
+    pub(crate) fn index(&self, obj_type: ObjType, name: &str) -> Option<usize> {
+        self.table(obj_type)?.ids.get(name).copied()
+    }
+
+    /// The name of the object of kind `obj_type` at offset `index`
+    pub(crate) fn name(&self, obj_type: ObjType, index: usize) -> Option<&str> {
+        self.table(obj_type)?.names.get(index)?.as_deref()
+    }
+
+    /// The number of objects of kind `obj_type`, named or not
+    pub(crate) fn len(&self, obj_type: ObjType) -> usize {
+        self.table(obj_type).map_or(0, |table| table.names.len())
+    }
+
+    /// The offsets and names of all named objects of kind `obj_type`, in order
+    pub(crate) fn iter(
+        &self,
+        obj_type: ObjType,
+    ) -> impl Iterator<Item = (usize, &str)> + '_ {
+        self.table(obj_type)
+            .into_iter()
+            .flat_map(|table| table.names.iter().enumerate())
+            .filter_map(|(index, name)| Some((index, name.as_deref()?)))
+    }
+}
+
+/// Converts a raw `mjtObj` value read from an `mjModel` array
+pub(crate) fn obj_type_from(val: c_int) -> ObjType {
+    usize::try_from(val)
+        .ok()
+        .and_then(|i| OBJ_TYPES.get(i).copied())
+        .expect("Invalid value for ObjType")
+}
+
+/// Every [`ObjType`], in order of discriminant
+const OBJ_TYPES: [ObjType; 25] = [
+    ObjType::UNKNOWN,
+    ObjType::BODY,
+    ObjType::XBODY,
+    ObjType::JOINT,
+    ObjType::DOF,
+    ObjType::GEOM,
+    ObjType::SITE,
+    ObjType::CAMERA,
+    ObjType::LIGHT,
+    ObjType::MESH,
+    ObjType::SKIN,
+    ObjType::HFIELD,
+    ObjType::TEXTURE,
+    ObjType::MATERIAL,
+    ObjType::PAIR,
+    ObjType::EXCLUDE,
+    ObjType::EQUALITY,
+    ObjType::TENDON,
+    ObjType::ACTUATOR,
+    ObjType::SENSOR,
+    ObjType::NUMERIC,
+    ObjType::TEXT,
+    ObjType::TUPLE,
+    ObjType::KEY,
+    ObjType::PLUGIN,
+];
@@ -0,0 +1,296 @@
+//! Typed access to the physics options of a [`Model`](crate::Model), such as
+//! the timestep, gravity, and solver settings.
+
+use std::os::raw::c_int;
+
+use mujoco_rs_sys::no_render::mjOption;
+use nalgebra::Vector3;
+
+use crate::re_exports::{Cone, DisableBit, EnableBit, Integrator, Solver};
+
+fn integrator_from(val: c_int) -> Integrator {
+    [
+        Integrator::EULER,
+        Integrator::RK4,
+        Integrator::IMPLICIT,
+        Integrator::IMPLICITFAST,
+    ]
+    .iter()
+    .copied()
+    .find(|&i| i as c_int == val)
+    .expect("Invalid value for Integrator")
+}
+
+fn solver_from(val: c_int) -> Solver {
+    [Solver::PGS, Solver::CG, Solver::NEWTON]
+        .iter()
+        .copied()
+        .find(|&s| s as c_int == val)
+        .expect("Invalid value for Solver")
+}
+
+fn cone_from(val: c_int) -> Cone {
+    [Cone::PYRAMIDAL, Cone::ELLIPTIC]
+        .iter()
+        .copied()
+        .find(|&c| c as c_int == val)
+        .expect("Invalid value for Cone")
+}
+
+/// The physics options of a model, corresponding to the `<option>` element of
+/// MJCF. Obtained with [`Model::options()`] and changed through
+/// [`Model::options_mut()`] or [`Simulation::options_mut()`], and takes effect
+/// from the next step.
+///
+/// [`Model::options()`]: crate::Model::options
+/// [`Model::options_mut()`]: crate::Model::options_mut
+/// [`Simulation::options_mut()`]: crate::Simulation::options_mut
+#[repr(transparent)]
+pub struct PhysicsOptions(mjOption);
+
+impl PhysicsOptions {
+    pub(crate) fn from_raw(opt: &mjOption) -> &Self {
+        // Sound because `PhysicsOptions` is a transparent wrapper
+        unsafe { &*(opt as *const mjOption as *const Self) }
+    }
+
+    pub(crate) fn from_raw_mut(opt: &mut mjOption) -> &mut Self {
+        unsafe { &mut *(opt as *mut mjOption as *mut Self) }
+    }
+
+    /// Gets the underlying [`mjOption`]
+    pub fn raw(&self) -> &mjOption {
+        &self.0
+    }
+
+    /// The timestep in seconds
+    pub fn timestep(&self) -> f64 {
+        self.0.timestep
+    }
+
+    pub fn set_timestep(&mut self, timestep: f64) {
+        self.0.timestep = timestep;
+    }
+
+    /// Gravitational acceleration in the world frame
+    pub fn gravity(&self) -> Vector3<f64> {
+        Vector3::from(self.0.gravity)
+    }
+
+    pub fn set_gravity(&mut self, gravity: Vector3<f64>) {
+        self.0.gravity = gravity.into();
+    }
+
+    /// Velocity of the medium, used for lift and drag
+    pub fn wind(&self) -> Vector3<f64> {
+        Vector3::from(self.0.wind)
+    }
+
+    pub fn set_wind(&mut self, wind: Vector3<f64>) {
+        self.0.wind = wind.into();
+    }
+
+    /// Global magnetic flux, as measured by magnetometers
+    pub fn magnetic(&self) -> Vector3<f64> {
+        Vector3::from(self.0.magnetic)
+    }
+
+    pub fn set_magnetic(&mut self, magnetic: Vector3<f64>) {
+        self.0.magnetic = magnetic.into();
+    }
+
+    /// Density of the medium. 0 disables lift and drag.
+    pub fn density(&self) -> f64 {
+        self.0.density
+    }
+
+    pub fn set_density(&mut self, density: f64) {
+        self.0.density = density;
+    }
+
+    /// Viscosity of the medium. 0 disables viscous forces.
+    pub fn viscosity(&self) -> f64 {
+        self.0.viscosity
+    }
+
+    pub fn set_viscosity(&mut self, viscosity: f64) {
+        self.0.viscosity = viscosity;
+    }
+
+    /// The numerical integrator
+    pub fn integrator(&self) -> Integrator {
+        integrator_from(self.0.integrator)
+    }
+
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.0.integrator = integrator as c_int;
+    }
+
+    /// The constraint solver algorithm
+    pub fn solver(&self) -> Solver {
+        solver_from(self.0.solver)
+    }
+
+    pub fn set_solver(&mut self, solver: Solver) {
+        self.0.solver = solver as c_int;
+    }
+
+    /// The maximum number of iterations of the constraint solver
+    pub fn iterations(&self) -> usize {
+        self.0.iterations as usize
+    }
+
+    /// Sets the maximum number of iterations of the constraint solver. MuJoCo
+    /// stores the count as a C `int`, so it is limited to `u16`, which any
+    /// `int` can hold.
+    pub fn set_iterations(&mut self, iterations: u16) {
+        self.0.iterations = c_int::from(iterations);
+    }
+
+    /// The tolerance at which the constraint solver stops early
+    pub fn tolerance(&self) -> f64 {
+        self.0.tolerance
+    }
+
+    pub fn set_tolerance(&mut self, tolerance: f64) {
+        self.0.tolerance = tolerance;
+    }
+
+    /// The type of friction cone
+    pub fn cone(&self) -> Cone {
+        cone_from(self.0.cone)
+    }
+
+    pub fn set_cone(&mut self, cone: Cone) {
+        self.0.cone = cone as c_int;
+    }
+
+    /// The set of disabled default features
+    pub fn disable_flags(&self) -> DisableBit {
+        DisableBit(self.0.disableflags as _)
+    }
+
+    pub fn set_disable_flags(&mut self, flags: DisableBit) {
+        self.0.disableflags = flags.0 as c_int;
+    }
+
+    /// The set of enabled optional features
+    pub fn enable_flags(&self) -> EnableBit {
+        EnableBit(self.0.enableflags as _)
+    }
+
+    pub fn set_enable_flags(&mut self, flags: EnableBit) {
+        self.0.enableflags = flags.0 as c_int;
+    }
+
+    /// Whether every feature in `flags` is disabled
+    pub fn is_disabled(&self, flags: DisableBit) -> bool {
+        self.disable_flags() & flags == flags
+    }
+
+    /// Disables or re-enables the features in `flags`
+    pub fn set_disabled(&mut self, flags: DisableBit, disabled: bool) {
+        let current = self.disable_flags().0;
+        let new = if disabled {
+            current | flags.0
+        } else {
+            current & !flags.0
+        };
+        self.set_disable_flags(DisableBit(new));
+    }
+
+    /// Whether every feature in `flags` is enabled
+    pub fn is_enabled(&self, flags: EnableBit) -> bool {
+        self.enable_flags() & flags == flags
+    }
+
+    /// Enables or disables the optional features in `flags`
+    pub fn set_enabled(&mut self, flags: EnableBit, enabled: bool) {
+        let current = self.enable_flags().0;
+        let new = if enabled {
+            current | flags.0
+        } else {
+            current & !flags.0
+        };
+        self.set_enable_flags(EnableBit(new));
+    }
+}
+
+impl std::fmt::Debug for PhysicsOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("PhysicsOptions")
+            .field("timestep", &self.timestep())
+            .field("gravity", &self.0.gravity)
+            .field("wind", &self.0.wind)
+            .field("magnetic", &self.0.magnetic)
+            .field("density", &self.density())
+            .field("viscosity", &self.viscosity())
+            .field("integrator", &self.integrator())
+            .field("solver", &self.solver())
+            .field("iterations", &self.iterations())
+            .field("tolerance", &self.tolerance())
+            .field("cone", &self.cone())
+            .field("disable_flags", &self.disable_flags())
+            .field("enable_flags", &self.enable_flags())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::SIMPLE_XML;
+    use crate::{Cone, DisableBit, EnableBit, Integrator, Model, Simulation, Solver};
+    use nalgebra::Vector3;
+
+    #[test]
+    fn from_mjcf() {
+        let xml = r#"<mujoco>
+    <option timestep="0.005" gravity="0 0 -1" density="1.2" integrator="RK4"
+            solver="CG" iterations="20" cone="elliptic">
+        <flag contact="disable" energy="enable"/>
+    </option>
+</mujoco>"#;
+        let m = Model::from_xml_str(xml).unwrap();
+        let opt = m.options();
+        assert_eq!(opt.timestep(), 0.005);
+        assert_eq!(opt.gravity(), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(opt.density(), 1.2);
+        assert_eq!(opt.integrator(), Integrator::RK4);
+        assert_eq!(opt.solver(), Solver::CG);
+        assert_eq!(opt.iterations(), 20);
+        assert_eq!(opt.cone(), Cone::ELLIPTIC);
+        assert!(opt.is_disabled(DisableBit::CONTACT));
+        assert!(!opt.is_disabled(DisableBit::GRAVITY));
+        assert!(opt.is_enabled(EnableBit::ENERGY));
+    }
+
+    #[test]
+    fn set_between_steps() {
+        let mut sim = Simulation::new(Model::from_xml_str(*SIMPLE_XML).unwrap());
+        sim.options_mut().set_timestep(0.01);
+        sim.options_mut().set_iterations(u16::MAX);
+        assert_eq!(sim.model().options().iterations(), 65535);
+        sim.options_mut().set_disabled(DisableBit::GRAVITY, true);
+        sim.step();
+        assert_eq!(sim.state().time(), 0.01);
+        assert!(sim.qvel().iter().all(|&v| v == 0.0));
+
+        sim.options_mut().set_disabled(DisableBit::GRAVITY, false);
+        sim.options_mut().set_gravity(Vector3::new(0.0, 0.0, -10.0));
+        sim.step();
+        assert!(sim.qvel()[2] < 0.0);
+        assert_eq!(sim.model().options().gravity().z, -10.0);
+    }
+
+    #[test]
+    fn copy_on_write() {
+        let model = std::sync::Arc::new(Model::from_xml_str(*SIMPLE_XML).unwrap());
+        let mut sim = Simulation::new(model.clone());
+        sim.options_mut().set_timestep(0.01);
+
+        // Other users of the model are unaffected
+        assert_eq!(model.options().timestep(), 0.002);
+        assert_eq!(sim.model().options().timestep(), 0.01);
+        assert_eq!(sim.model().name_to_id("body1"), Some(crate::BodyId::new(1)));
+    }
+}
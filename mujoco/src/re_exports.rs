@@ -2,5 +2,29 @@
 
 // See [here](http://www.mujoco.org/book/APIreference.html#mjtGeom) for more info.
 pub use mujoco_rs_sys::no_render::mjtGeom as GeomType;
+// See [here](http://www.mujoco.org/book/APIreference.html#mjtJoint) for more info.
+pub use mujoco_rs_sys::no_render::mjtJoint as JointType;
+// See [here](https://mujoco.readthedocs.io/en/2.3.5/APIreference.html#mjtdyn) for more info.
+pub use mujoco_rs_sys::no_render::mjtDyn as DynType;
+// See [here](https://mujoco.readthedocs.io/en/2.3.5/APIreference.html#mjtgain) for more info.
+pub use mujoco_rs_sys::no_render::mjtGain as GainType;
+// See [here](https://mujoco.readthedocs.io/en/2.3.5/APIreference.html#mjtbias) for more info.
+pub use mujoco_rs_sys::no_render::mjtBias as BiasType;
+// See [here](https://mujoco.readthedocs.io/en/2.3.5/APIreference.html#mjtsensor) for more info.
+pub use mujoco_rs_sys::no_render::mjtSensor as SensorType;
+// See [here](https://mujoco.readthedocs.io/en/2.3.5/APIreference.html#mjtdatatype) for more info.
+pub use mujoco_rs_sys::no_render::mjtDataType as SensorDataType;
+// See [here](https://mujoco.readthedocs.io/en/2.3.5/APIreference.html#mjtstage) for more info.
+pub use mujoco_rs_sys::no_render::mjtStage as Stage;
 // See [here](http://www.mujoco.org/book/APIreference.html#mjtObj) for more info.
 pub use mujoco_rs_sys::no_render::mjtObj as ObjType;
+// See [here](https://mujoco.readthedocs.io/en/2.3.5/APIreference.html#mjtintegrator) for more info.
+pub use mujoco_rs_sys::no_render::mjtIntegrator as Integrator;
+// See [here](https://mujoco.readthedocs.io/en/2.3.5/APIreference.html#mjtsolver) for more info.
+pub use mujoco_rs_sys::no_render::mjtSolver as Solver;
+// See [here](https://mujoco.readthedocs.io/en/2.3.5/APIreference.html#mjtcone) for more info.
+pub use mujoco_rs_sys::no_render::mjtCone as Cone;
+// See [here](https://mujoco.readthedocs.io/en/2.3.5/APIreference.html#mjtdisablebit) for more info.
+pub use mujoco_rs_sys::no_render::mjtDisableBit as DisableBit;
+// See [here](https://mujoco.readthedocs.io/en/2.3.5/APIreference.html#mjtenablebit) for more info.
+pub use mujoco_rs_sys::no_render::mjtEnableBit as EnableBit;
@@ -0,0 +1,237 @@
+use std::os::raw::c_int;
+
+use mujoco_rs_sys::no_render::mjModel;
+use nalgebra::{Quaternion, Vector3};
+
+use crate::id::{
+    index_from_raw, ActuatorId, BodyId, CameraId, GeomId, JointId, SensorId, SiteId,
+    TendonId,
+};
+use crate::names::obj_type_from;
+use crate::re_exports::{ObjType, SensorDataType, SensorType, Stage};
+
+/// Every [`SensorType`], so that raw values can be matched against the
+/// discriminants that the bindings were generated with
+const SENSOR_TYPES: [SensorType; 39] = [
+    SensorType::TOUCH,
+    SensorType::ACCELEROMETER,
+    SensorType::VELOCIMETER,
+    SensorType::GYRO,
+    SensorType::FORCE,
+    SensorType::TORQUE,
+    SensorType::MAGNETOMETER,
+    SensorType::RANGEFINDER,
+    SensorType::JOINTPOS,
+    SensorType::JOINTVEL,
+    SensorType::TENDONPOS,
+    SensorType::TENDONVEL,
+    SensorType::ACTUATORPOS,
+    SensorType::ACTUATORVEL,
+    SensorType::ACTUATORFRC,
+    SensorType::JOINTACTFRC,
+    SensorType::BALLQUAT,
+    SensorType::BALLANGVEL,
+    SensorType::JOINTLIMITPOS,
+    SensorType::JOINTLIMITVEL,
+    SensorType::JOINTLIMITFRC,
+    SensorType::TENDONLIMITPOS,
+    SensorType::TENDONLIMITVEL,
+    SensorType::TENDONLIMITFRC,
+    SensorType::FRAMEPOS,
+    SensorType::FRAMEQUAT,
+    SensorType::FRAMEXAXIS,
+    SensorType::FRAMEYAXIS,
+    SensorType::FRAMEZAXIS,
+    SensorType::FRAMELINVEL,
+    SensorType::FRAMEANGVEL,
+    SensorType::FRAMELINACC,
+    SensorType::FRAMEANGACC,
+    SensorType::SUBTREECOM,
+    SensorType::SUBTREELINVEL,
+    SensorType::SUBTREEANGMOM,
+    SensorType::CLOCK,
+    SensorType::PLUGIN,
+    SensorType::USER,
+];
+
+pub fn sensor_type_from(val: c_int) -> SensorType {
+    SENSOR_TYPES
+        .iter()
+        .copied()
+        .find(|&t| t as c_int == val)
+        .expect("Invalid value for SensorType")
+}
+
+pub fn sensor_data_type_from(val: c_int) -> SensorDataType {
+    [
+        SensorDataType::REAL,
+        SensorDataType::POSITIVE,
+        SensorDataType::AXIS,
+        SensorDataType::QUATERNION,
+    ]
+    .iter()
+    .copied()
+    .find(|&t| t as c_int == val)
+    .expect("Invalid value for SensorDataType")
+}
+
+pub fn stage_from(val: c_int) -> Stage {
+    [Stage::NONE, Stage::POS, Stage::VEL, Stage::ACC]
+        .iter()
+        .copied()
+        .find(|&s| s as c_int == val)
+        .expect("Invalid value for Stage")
+}
+
+/// The object that a sensor is attached to, or measures relative to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SensorObject {
+    /// The inertial frame of a body, centered on its center of mass
+    Body(BodyId),
+    /// The frame of a body
+    XBody(BodyId),
+    Joint(JointId),
+    Geom(GeomId),
+    Site(SiteId),
+    Camera(CameraId),
+    Tendon(TendonId),
+    Actuator(ActuatorId),
+    /// An object of a kind that doesn't have an id type, such as a plugin
+    Other(
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::ObjTypeDef"))]
+        ObjType,
+        usize,
+    ),
+}
+
+impl SensorObject {
+    /// Builds the object of kind `obj_type` with index `id`, if there is one
+    fn from_raw(obj_type: c_int, id: c_int) -> Option<Self> {
+        let id = index_from_raw(id)?;
+        Some(match obj_type_from(obj_type) {
+            ObjType::UNKNOWN => return None,
+            ObjType::BODY => Self::Body(BodyId::new(id)),
+            ObjType::XBODY => Self::XBody(BodyId::new(id)),
+            ObjType::JOINT => Self::Joint(JointId::new(id)),
+            ObjType::GEOM => Self::Geom(GeomId::new(id)),
+            ObjType::SITE => Self::Site(SiteId::new(id)),
+            ObjType::CAMERA => Self::Camera(CameraId::new(id)),
+            ObjType::TENDON => Self::Tendon(TendonId::new(id)),
+            ObjType::ACTUATOR => Self::Actuator(ActuatorId::new(id)),
+            obj_type => Self::Other(obj_type, id),
+        })
+    }
+}
+
+/// A sensor of a [`Model`](crate::Model)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sensor {
+    pub id: SensorId,
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::SensorTypeDef"))]
+    pub sensor_type: SensorType,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_impls::SensorDataTypeDef")
+    )]
+    pub data_type: SensorDataType,
+    /// The computation stage that must have run for the sensor's data to be
+    /// up to date
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::StageDef"))]
+    pub stage: Stage,
+    /// The object that the sensor is attached to, if any
+    pub object: Option<SensorObject>,
+    /// The object that frame sensors measure relative to, if any
+    pub reference: Option<SensorObject>,
+    /// Offset of the sensor's values in [`Simulation::sensordata()`]
+    ///
+    /// [`Simulation::sensordata()`]: crate::Simulation::sensordata
+    pub addr: usize,
+    /// The number of values the sensor produces
+    pub dim: usize,
+    /// Standard deviation of the noise added when sensor noise is enabled
+    pub noise: f64,
+    /// The value that the sensor's output is clipped to, or 0 for no clipping
+    pub cutoff: f64,
+}
+
+impl Sensor {
+    /// Reads sensor `i` out of `m`
+    ///
+    /// # Safety
+    /// `i` must be less than `m.nsensor`.
+    pub(crate) unsafe fn from_model(m: &mjModel, i: usize, name: &str) -> Self {
+        Self {
+            id: SensorId::new(i),
+            name: name.to_owned(),
+            sensor_type: sensor_type_from(*m.sensor_type.add(i)),
+            data_type: sensor_data_type_from(*m.sensor_datatype.add(i)),
+            stage: stage_from(*m.sensor_needstage.add(i)),
+            object: SensorObject::from_raw(
+                *m.sensor_objtype.add(i),
+                *m.sensor_objid.add(i),
+            ),
+            reference: SensorObject::from_raw(
+                *m.sensor_reftype.add(i),
+                *m.sensor_refid.add(i),
+            ),
+            addr: *m.sensor_adr.add(i) as usize,
+            dim: *m.sensor_dim.add(i) as usize,
+            noise: *m.sensor_noise.add(i),
+            cutoff: *m.sensor_cutoff.add(i),
+        }
+    }
+}
+
+/// The reading of a single sensor, shaped according to its dimension and data
+/// type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SensorValue<'a> {
+    Scalar(f64),
+    Vector3(Vector3<f64>),
+    /// An orientation, such as from a `framequat` or `ballquat` sensor
+    Quaternion(Quaternion<f64>),
+    /// The values of sensors with any other dimension, such as user and plugin
+    /// sensors
+    Other(&'a [f64]),
+}
+
+impl<'a> SensorValue<'a> {
+    /// Shapes the values of a sensor with data type `data_type`
+    pub(crate) fn new(data_type: SensorDataType, values: &'a [f64]) -> Self {
+        match (values.len(), data_type) {
+            (1, _) => Self::Scalar(values[0]),
+            (3, _) => Self::Vector3(Vector3::from_column_slice(values)),
+            (4, SensorDataType::QUATERNION) => Self::Quaternion(Quaternion::new(
+                values[0], values[1], values[2], values[3],
+            )),
+            _ => Self::Other(values),
+        }
+    }
+
+    /// The value as a scalar, if it is one
+    pub fn as_scalar(&self) -> Option<f64> {
+        match self {
+            Self::Scalar(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    /// The value as a vector, if it is one
+    pub fn as_vector3(&self) -> Option<Vector3<f64>> {
+        match self {
+            Self::Vector3(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// The value as a quaternion, if it is one
+    pub fn as_quaternion(&self) -> Option<Quaternion<f64>> {
+        match self {
+            Self::Quaternion(q) => Some(*q),
+            _ => None,
+        }
+    }
+}
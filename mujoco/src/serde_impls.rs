@@ -0,0 +1,131 @@
+//! Serialization of the enums re-exported from the bindings, which can't derive
+//! `Serialize` and `Deserialize` themselves. Fields of these types are tagged
+//! with `#[serde(with = "...")]` to go through the mirrors defined here.
+
+use serde::{Deserialize, Serialize};
+
+use crate::re_exports::{
+    BiasType, DynType, GainType, GeomType, JointType, ObjType, SensorDataType,
+    SensorType, Stage,
+};
+
+/// Defines `$def` as a mirror of the enum `$remote` from the bindings,
+/// serialized by variant name. The discriminants are taken from the bindings,
+/// and serde matches on every variant of `$remote`, so a mirror that is out of
+/// date with the bindings doesn't compile.
+macro_rules! mirror_enum {
+    ($def:ident, $remote:ident, $path:literal, [$($variant:ident),* $(,)?]) => {
+        #[doc = concat!("Mirror of [`", stringify!($remote), "`]")]
+        #[derive(Serialize, Deserialize)]
+        #[serde(remote = $path)]
+        #[allow(clippy::upper_case_acronyms)]
+        pub(crate) enum $def {
+            $($variant = $remote::$variant as isize,)*
+        }
+    };
+}
+
+mirror_enum!(
+    GeomTypeDef,
+    GeomType,
+    "GeomType",
+    [
+        PLANE, HFIELD, SPHERE, CAPSULE, ELLIPSOID, CYLINDER, BOX, MESH, NGEOMTYPES,
+        ARROW, ARROW1, ARROW2, LINE, SKIN, LABEL, NONE,
+    ]
+);
+
+mirror_enum!(
+    DynTypeDef,
+    DynType,
+    "DynType",
+    [NONE, INTEGRATOR, FILTER, MUSCLE, USER]
+);
+
+mirror_enum!(
+    GainTypeDef,
+    GainType,
+    "GainType",
+    [FIXED, AFFINE, MUSCLE, USER]
+);
+
+mirror_enum!(
+    BiasTypeDef,
+    BiasType,
+    "BiasType",
+    [NONE, AFFINE, MUSCLE, USER]
+);
+
+mirror_enum!(
+    JointTypeDef,
+    JointType,
+    "JointType",
+    [FREE, BALL, SLIDE, HINGE]
+);
+
+mirror_enum!(
+    SensorTypeDef,
+    SensorType,
+    "SensorType",
+    [
+        TOUCH,
+        ACCELEROMETER,
+        VELOCIMETER,
+        GYRO,
+        FORCE,
+        TORQUE,
+        MAGNETOMETER,
+        RANGEFINDER,
+        JOINTPOS,
+        JOINTVEL,
+        TENDONPOS,
+        TENDONVEL,
+        ACTUATORPOS,
+        ACTUATORVEL,
+        ACTUATORFRC,
+        JOINTACTFRC,
+        BALLQUAT,
+        BALLANGVEL,
+        JOINTLIMITPOS,
+        JOINTLIMITVEL,
+        JOINTLIMITFRC,
+        TENDONLIMITPOS,
+        TENDONLIMITVEL,
+        TENDONLIMITFRC,
+        FRAMEPOS,
+        FRAMEQUAT,
+        FRAMEXAXIS,
+        FRAMEYAXIS,
+        FRAMEZAXIS,
+        FRAMELINVEL,
+        FRAMEANGVEL,
+        FRAMELINACC,
+        FRAMEANGACC,
+        SUBTREECOM,
+        SUBTREELINVEL,
+        SUBTREEANGMOM,
+        CLOCK,
+        PLUGIN,
+        USER,
+    ]
+);
+
+mirror_enum!(
+    SensorDataTypeDef,
+    SensorDataType,
+    "SensorDataType",
+    [REAL, POSITIVE, AXIS, QUATERNION]
+);
+
+mirror_enum!(StageDef, Stage, "Stage", [NONE, POS, VEL, ACC]);
+
+mirror_enum!(
+    ObjTypeDef,
+    ObjType,
+    "ObjType",
+    [
+        UNKNOWN, BODY, XBODY, JOINT, DOF, GEOM, SITE, CAMERA, LIGHT, MESH, SKIN,
+        HFIELD, TEXTURE, MATERIAL, PAIR, EXCLUDE, EQUALITY, TENDON, ACTUATOR, SENSOR,
+        NUMERIC, TEXT, TUPLE, KEY, PLUGIN,
+    ]
+);
@@ -1,173 +1,1362 @@
-use nalgebra::{Quaternion, Vector3, Vector6};
+use std::sync::Arc;
+
+use nalgebra::{
+    Isometry3, Matrix3, MatrixView3xX, MatrixView4xX, MatrixView6xX, MatrixViewMut6xX,
+    Quaternion, Rotation3, UnitQuaternion, Vector3, Vector6,
+};
 
 use crate::{
-    helpers::{extract_vector_float, Local, LocalFloat},
-    Model, State,
+    helpers::borrow_slice,
+    joint::{joint_nq, joint_nv, joint_type_from},
+    sensor::sensor_data_type_from,
+    ActuatorId, BodyId, CameraId, Contact, Error, GeomId, JointId, JointPosition,
+    JointType, JointVelocity, KeyId, Keyframe, Model, ObjectId, ObjectRef,
+    PhysicsOptions, SensorId, SensorValue, SiteId, Stage, State,
 };
 
+/// A [`State`] together with the functions that advance and inspect it. Anything
+/// that changes the state takes `&mut self`.
+///
+/// Functions that take an object by name or id fail with
+/// [`Error::NoSuchObject`] if the model has no such object.
 pub struct Simulation {
-    pub state: State,
-    pub model: Model,
+    state: State,
+    clamp_controls: bool,
 }
 
 impl Simulation {
-    pub fn new(model: Model) -> Self {
-        let state = State::new(&model);
-        Self { state, model }
+    /// Creates a `Simulation` in the default state of `model`
+    pub fn new(model: impl Into<Arc<Model>>) -> Self {
+        Self::from_state(State::new(model))
+    }
+
+    /// Creates a `Simulation` that continues from `state`
+    pub fn from_state(state: State) -> Self {
+        Self {
+            state,
+            clamp_controls: false,
+        }
+    }
+
+    /// The [`Model`] being simulated
+    pub fn model(&self) -> &Arc<Model> {
+        &self.state.model
+    }
+
+    /// Gets the physics options of the model being simulated for
+    /// modification, such as to change the timestep between steps.
+    ///
+    /// If the model is shared with other states, it is first copied so that
+    /// they are unaffected.
+    pub fn options_mut(&mut self) -> &mut PhysicsOptions {
+        Arc::make_mut(&mut self.state.model).options_mut()
+    }
+
+    /// The current [`State`] of the simulation
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// Mutable counterpart of [`Simulation::state()`]
+    pub fn state_mut(&mut self) -> &mut State {
+        &mut self.state
+    }
+
+    /// Consumes the `Simulation`, returning its [`State`]
+    pub fn into_state(self) -> State {
+        self.state
     }
 
-    /// Set control vector
-    pub fn control(&self, control: &[f64]) {
-        let mj_data = self.state.ptr;
-        let raw_vec = unsafe { (*mj_data).ctrl };
+    /// Sets the control vector, which must have one finite entry per actuator.
+    /// Nothing is written unless every entry is valid.
+    ///
+    /// If [`Simulation::set_clamp_controls()`] is enabled, the controls of
+    /// actuators with a `ctrlrange` are clamped to it.
+    pub fn control(&mut self, control: &[f64]) -> Result<(), Error> {
+        let nu = self.model().nu();
+        if control.len() != nu {
+            return Err(Error::ControlLength {
+                expected: nu,
+                actual: control.len(),
+            });
+        }
+        if let Some(i) = control.iter().position(|c| !c.is_finite()) {
+            return Err(Error::NonFiniteControl(ActuatorId::new(i)));
+        }
+        for (i, &value) in control.iter().enumerate() {
+            let value = self.clamp_control(i, value);
+            self.ctrl_mut()[i] = value;
+        }
+        Ok(())
+    }
 
-        if control.len() != self.model.nu() {
-            return;
+    /// Sets the control of a single actuator, looked up by name or id. See
+    /// [`Simulation::control()`].
+    pub fn control_actuator(
+        &mut self,
+        actuator: impl ObjectRef<ActuatorId>,
+        value: f64,
+    ) -> Result<(), Error> {
+        let id = self.resolve(actuator)?;
+        if !value.is_finite() {
+            return Err(Error::NonFiniteControl(id));
         }
+        let value = self.clamp_control(id.index(), value);
+        self.ctrl_mut()[id.index()] = value;
+        Ok(())
+    }
+
+    /// Sets whether [`Simulation::control()`] and
+    /// [`Simulation::control_actuator()`] clamp controls to the `ctrlrange` of
+    /// their actuators. Off by default, in which case MuJoCo still clamps the
+    /// controls that it acts on, but [`Simulation::ctrl()`] reports them as
+    /// they were set.
+    pub fn set_clamp_controls(&mut self, clamp: bool) {
+        self.clamp_controls = clamp;
+    }
 
-        for (i, item) in control.iter().enumerate().take(self.model.nu()) {
-            unsafe { *raw_vec.add(i) = *item };
+    /// Clamps `value` to the `ctrlrange` of actuator `i`, if clamping is
+    /// enabled and the actuator is limited
+    fn clamp_control(&self, i: usize, value: f64) -> f64 {
+        let m = unsafe { &*self.model().ptr() };
+        let limited = unsafe { *m.actuator_ctrllimited.add(i) } != 0;
+        if !self.clamp_controls || !limited {
+            return value;
         }
+        let range = unsafe { borrow_slice(m.actuator_ctrlrange.add(2 * i), 2) };
+        value.max(range[0]).min(range[1])
     }
 
-    /// Evaulate constraint forces and sensors
-    pub fn evaluate_sensors(&self) {
+    /// Evaluates the sensors, along with the constraint forces and everything
+    /// else that they depend on. Equivalent to [`Simulation::forward()`].
+    pub fn evaluate_sensors(&mut self) {
+        self.forward();
+    }
+
+    /// Advance simulation by one step
+    pub fn step(&mut self) {
         unsafe {
-            mujoco_rs_sys::no_render::mj_sensorPos(self.model.ptr(), self.state.ptr());
-            mujoco_rs_sys::no_render::mj_sensorVel(self.model.ptr(), self.state.ptr());
-            mujoco_rs_sys::no_render::mj_sensorAcc(self.model.ptr(), self.state.ptr());
+            mujoco_rs_sys::no_render::mj_step(self.model().ptr(), self.state.ptr());
         };
     }
 
-    /// Advance simulation by one step
-    pub fn step(&self) {
+    /// Runs the first half of a step: everything that depends on the positions
+    /// and velocities but not on the controls. Set the controls based on the
+    /// fresh kinematics and sensors, then call [`Simulation::step2()`] to
+    /// finish the step.
+    ///
+    /// Splitting a step this way is only equivalent to [`Simulation::step()`]
+    /// with the Euler and implicit integrators. With RK4, the step is done
+    /// with Euler instead.
+    pub fn step1(&mut self) {
+        unsafe {
+            mujoco_rs_sys::no_render::mj_step1(self.model().ptr(), self.state.ptr())
+        };
+    }
+
+    /// Runs the second half of a step started with [`Simulation::step1()`],
+    /// computing the accelerations from the controls and advancing the time
+    pub fn step2(&mut self) {
         unsafe {
-            mujoco_rs_sys::no_render::mj_step(self.model.ptr(), self.state.ptr());
+            mujoco_rs_sys::no_render::mj_step2(self.model().ptr(), self.state.ptr())
         };
     }
 
-    /// Returns positions of bodies in inertial frame
-    pub fn xpos(&self) -> Vec<Vector3<f64>> {
-        let mj_data = self.state.ptr();
-        let raw_vec = unsafe { (*mj_data).xpos };
+    /// Runs forward dynamics: computes the accelerations and every quantity
+    /// derived from the current state, without advancing the time
+    pub fn forward(&mut self) {
+        unsafe {
+            mujoco_rs_sys::no_render::mj_forward(self.model().ptr(), self.state.ptr())
+        };
+    }
+
+    /// Like [`Simulation::forward()`], but skips the stages up to and including
+    /// `skip_stage`, whose results must already be up to date. For example,
+    /// when only the velocities have changed, the position stage can be skipped
+    /// with [`Stage::POS`]. The sensors are not computed if `skip_sensor` is
+    /// set.
+    pub fn forward_skip(&mut self, skip_stage: Stage, skip_sensor: bool) {
+        unsafe {
+            mujoco_rs_sys::no_render::mj_forwardSkip(
+                self.model().ptr(),
+                self.state.ptr(),
+                skip_stage as std::os::raw::c_int,
+                skip_sensor as std::os::raw::c_int,
+            )
+        };
+    }
+
+    /// Runs inverse dynamics: computes the generalized forces that produce the
+    /// accelerations in [`Simulation::qacc_mut()`], which are then available
+    /// from [`Simulation::qfrc_inverse()`]
+    pub fn inverse(&mut self) {
+        unsafe {
+            mujoco_rs_sys::no_render::mj_inverse(self.model().ptr(), self.state.ptr())
+        };
+    }
+
+    /// Like [`Simulation::inverse()`], but skips stages as in
+    /// [`Simulation::forward_skip()`]
+    pub fn inverse_skip(&mut self, skip_stage: Stage, skip_sensor: bool) {
+        unsafe {
+            mujoco_rs_sys::no_render::mj_inverseSkip(
+                self.model().ptr(),
+                self.state.ptr(),
+                skip_stage as std::os::raw::c_int,
+                skip_sensor as std::os::raw::c_int,
+            )
+        };
+    }
 
-        let raw_xpos: Vec<f64> =
-            extract_vector_float(raw_vec as *mut Local<f64>, 3, self.model.ngeom())
-                .iter()
-                .map(|e| e.to_f64())
-                .collect();
+    /// Computes the poses of the bodies, geoms, sites and cameras from the
+    /// positions, which is enough to update [`Simulation::body_pose()`] and
+    /// the like after changing `qpos`
+    pub fn kinematics(&mut self) {
+        unsafe {
+            mujoco_rs_sys::no_render::mj_kinematics(
+                self.model().ptr(),
+                self.state.ptr(),
+            )
+        };
+    }
 
-        let mut xpos: Vec<Vector3<f64>> = Vec::new();
+    /// Computes the centers of mass of the subtrees, and the inertias and
+    /// motion axes in the frames used by the dynamics. Requires
+    /// [`Simulation::kinematics()`] to be up to date.
+    pub fn com_pos(&mut self) {
+        unsafe {
+            mujoco_rs_sys::no_render::mj_comPos(self.model().ptr(), self.state.ptr())
+        };
+    }
 
-        for i in 0..self.model.ngeom() {
-            let entry = Vector3::<f64>::new(
-                raw_xpos[i * 3],
-                raw_xpos[i * 3 + 1],
-                raw_xpos[i * 3 + 2],
-            );
-            xpos.push(entry);
+    /// Runs the whole position stage of forward dynamics: kinematics, centers of
+    /// mass, tendons, the mass matrix, collision detection and constraints
+    pub fn fwd_position(&mut self) {
+        unsafe {
+            mujoco_rs_sys::no_render::mj_fwdPosition(
+                self.model().ptr(),
+                self.state.ptr(),
+            )
+        };
+    }
+
+    /// Returns the positions of the bodies in the world frame, one column per
+    /// body
+    pub fn xpos(&self) -> MatrixView3xX<'_, f64> {
+        self.state.xpos()
+    }
+
+    /// Returns the orientations of the bodies in the world frame as `w, x, y,
+    /// z` quaternions, one column per body
+    pub fn xquat(&self) -> MatrixView4xX<'_, f64> {
+        self.state.xquat()
+    }
+
+    /// Returns the pose of a body's frame in the world frame
+    pub fn body_pose(
+        &self,
+        body: impl ObjectRef<BodyId>,
+    ) -> Result<Isometry3<f64>, Error> {
+        Ok(self.body_pose_of(self.resolve(body)?))
+    }
+
+    /// [`Simulation::body_pose()`] for a body that has already been resolved
+    pub(crate) fn body_pose_of(&self, body: BodyId) -> Isometry3<f64> {
+        let i = body.index();
+        let (xpos, xquat) =
+            unsafe { ((*self.state.ptr).xpos, (*self.state.ptr).xquat) };
+        let pos = self.data_slice(unsafe { xpos.add(3 * i) }, 3);
+        let quat = self.data_slice(unsafe { xquat.add(4 * i) }, 4);
+        let quat = Quaternion::new(quat[0], quat[1], quat[2], quat[3]);
+        Isometry3::from_parts(
+            Vector3::from_column_slice(pos).into(),
+            UnitQuaternion::new_normalize(quat),
+        )
+    }
+
+    /// Returns the pose of a body's inertial frame, centered on its center of
+    /// mass and aligned with its principal axes of inertia, in the world frame
+    pub fn body_inertial_pose(
+        &self,
+        body: impl ObjectRef<BodyId>,
+    ) -> Result<Isometry3<f64>, Error> {
+        let i = self.resolve(body)?.index();
+        let (xipos, ximat) =
+            unsafe { ((*self.state.ptr).xipos, (*self.state.ptr).ximat) };
+        Ok(self.pose(xipos, ximat, i))
+    }
+
+    /// Returns the pose of a geom in the world frame
+    pub fn geom_pose(
+        &self,
+        geom: impl ObjectRef<GeomId>,
+    ) -> Result<Isometry3<f64>, Error> {
+        let i = self.resolve(geom)?.index();
+        let (xpos, xmat) =
+            unsafe { ((*self.state.ptr).geom_xpos, (*self.state.ptr).geom_xmat) };
+        Ok(self.pose(xpos, xmat, i))
+    }
+
+    /// Returns the pose of a site in the world frame
+    pub fn site_pose(
+        &self,
+        site: impl ObjectRef<SiteId>,
+    ) -> Result<Isometry3<f64>, Error> {
+        let i = self.resolve(site)?.index();
+        let (xpos, xmat) =
+            unsafe { ((*self.state.ptr).site_xpos, (*self.state.ptr).site_xmat) };
+        Ok(self.pose(xpos, xmat, i))
+    }
+
+    /// Returns the pose of a camera in the world frame. As in MuJoCo, the
+    /// camera looks along its negative z axis.
+    pub fn camera_pose(
+        &self,
+        camera: impl ObjectRef<CameraId>,
+    ) -> Result<Isometry3<f64>, Error> {
+        let i = self.resolve(camera)?.index();
+        let (xpos, xmat) =
+            unsafe { ((*self.state.ptr).cam_xpos, (*self.state.ptr).cam_xmat) };
+        Ok(self.pose(xpos, xmat, i))
+    }
+
+    /// Builds the pose of object `i` from per-object arrays of positions and
+    /// row-major rotation matrices
+    fn pose(&self, xpos: *const f64, xmat: *const f64, i: usize) -> Isometry3<f64> {
+        let pos = self.data_slice(unsafe { xpos.add(3 * i) }, 3);
+        let mat = self.data_slice(unsafe { xmat.add(9 * i) }, 9);
+        let rot = Rotation3::from_matrix_unchecked(Matrix3::from_row_slice(mat));
+        Isometry3::from_parts(
+            Vector3::from_column_slice(pos).into(),
+            UnitQuaternion::from_rotation_matrix(&rot),
+        )
+    }
+
+    /// Returns generalized positions
+    pub fn qpos(&self) -> &[f64] {
+        self.state.qpos()
+    }
+
+    /// Mutable counterpart of [`Simulation::qpos()`]
+    pub fn qpos_mut(&mut self) -> &mut [f64] {
+        self.state.qpos_mut()
+    }
+
+    /// Returns generalized velocities
+    pub fn qvel(&self) -> &[f64] {
+        self.state.qvel()
+    }
+
+    /// Mutable counterpart of [`Simulation::qvel()`]
+    pub fn qvel_mut(&mut self) -> &mut [f64] {
+        self.state.qvel_mut()
+    }
+
+    /// Returns generalized accelerations
+    pub fn qacc(&self) -> &[f64] {
+        self.state.qacc()
+    }
+
+    /// Mutable counterpart of [`Simulation::qacc()`], to set the accelerations
+    /// for [`Simulation::inverse()`]
+    pub fn qacc_mut(&mut self) -> &mut [f64] {
+        self.state.qacc_mut()
+    }
+
+    /// Returns the generalized forces computed by [`Simulation::inverse()`]
+    pub fn qfrc_inverse(&self) -> &[f64] {
+        self.state.qfrc_inverse()
+    }
+
+    /// Returns actuator activations
+    pub fn act(&self) -> &[f64] {
+        self.state.act()
+    }
+
+    /// Mutable counterpart of [`Simulation::act()`]
+    pub fn act_mut(&mut self) -> &mut [f64] {
+        self.state.act_mut()
+    }
+
+    /// Returns the control vector
+    pub fn ctrl(&self) -> &[f64] {
+        self.state.ctrl()
+    }
+
+    /// Mutable counterpart of [`Simulation::ctrl()`]
+    pub fn ctrl_mut(&mut self) -> &mut [f64] {
+        self.state.ctrl_mut()
+    }
+
+    /// Returns the generalized positions of a joint, looked up by name or id.
+    /// See [`Simulation::joint_position()`] for a typed alternative.
+    pub fn joint_qpos(&self, joint: impl ObjectRef<JointId>) -> Result<&[f64], Error> {
+        let (_, joint_type, qpos_addr, _) = self.joint_layout(joint)?;
+        Ok(&self.qpos()[qpos_addr..qpos_addr + joint_nq(joint_type)])
+    }
+
+    /// Mutable counterpart of [`Simulation::joint_qpos()`]
+    pub fn joint_qpos_mut(
+        &mut self,
+        joint: impl ObjectRef<JointId>,
+    ) -> Result<&mut [f64], Error> {
+        let (_, joint_type, qpos_addr, _) = self.joint_layout(joint)?;
+        Ok(&mut self.qpos_mut()[qpos_addr..qpos_addr + joint_nq(joint_type)])
+    }
+
+    /// Returns the generalized velocities of a joint, looked up by name or id.
+    /// See [`Simulation::joint_velocity()`] for a typed alternative.
+    pub fn joint_qvel(&self, joint: impl ObjectRef<JointId>) -> Result<&[f64], Error> {
+        let (_, joint_type, _, dof_addr) = self.joint_layout(joint)?;
+        Ok(&self.qvel()[dof_addr..dof_addr + joint_nv(joint_type)])
+    }
+
+    /// Mutable counterpart of [`Simulation::joint_qvel()`]
+    pub fn joint_qvel_mut(
+        &mut self,
+        joint: impl ObjectRef<JointId>,
+    ) -> Result<&mut [f64], Error> {
+        let (_, joint_type, _, dof_addr) = self.joint_layout(joint)?;
+        Ok(&mut self.qvel_mut()[dof_addr..dof_addr + joint_nv(joint_type)])
+    }
+
+    /// Returns the position of a joint, with the orientations of ball and free
+    /// joints as quaternions
+    pub fn joint_position(
+        &self,
+        joint: impl ObjectRef<JointId>,
+    ) -> Result<JointPosition, Error> {
+        let (_, joint_type, qpos_addr, _) = self.joint_layout(joint)?;
+        let qpos = &self.qpos()[qpos_addr..qpos_addr + joint_nq(joint_type)];
+        Ok(JointPosition::from_qpos(joint_type, qpos))
+    }
+
+    /// Sets the position of a joint, which must be of the same type as
+    /// `position`
+    pub fn set_joint_position(
+        &mut self,
+        joint: impl ObjectRef<JointId>,
+        position: JointPosition,
+    ) -> Result<(), Error> {
+        let (id, joint_type, qpos_addr, _) = self.joint_layout(joint)?;
+        if position.joint_type() != joint_type {
+            return Err(Error::JointTypeMismatch {
+                joint: id,
+                joint_type,
+            });
         }
+        let nq = joint_nq(joint_type);
+        position.write_qpos(&mut self.qpos_mut()[qpos_addr..qpos_addr + nq]);
+        Ok(())
+    }
 
-        xpos
+    /// Returns the velocity of a joint
+    pub fn joint_velocity(
+        &self,
+        joint: impl ObjectRef<JointId>,
+    ) -> Result<JointVelocity, Error> {
+        let (_, joint_type, _, dof_addr) = self.joint_layout(joint)?;
+        let qvel = &self.qvel()[dof_addr..dof_addr + joint_nv(joint_type)];
+        Ok(JointVelocity::from_qvel(joint_type, qvel))
     }
 
-    /// Returns rotations of bodies
-    pub fn xquat(&self) -> Vec<Quaternion<f64>> {
-        let mj_data = self.state.ptr();
-        let raw_vec = unsafe { (*mj_data).xquat };
-        let raw_quat: Vec<f64> =
-            extract_vector_float(raw_vec as *mut Local<f64>, 4, self.model.ngeom())
-                .iter()
-                .map(|e| e.to_f64())
-                .collect();
-        let mut xquat: Vec<Quaternion<f64>> = Vec::new();
+    /// Sets the velocity of a joint, which must be of the same type as
+    /// `velocity`
+    pub fn set_joint_velocity(
+        &mut self,
+        joint: impl ObjectRef<JointId>,
+        velocity: JointVelocity,
+    ) -> Result<(), Error> {
+        let (id, joint_type, _, dof_addr) = self.joint_layout(joint)?;
+        if velocity.joint_type() != joint_type {
+            return Err(Error::JointTypeMismatch {
+                joint: id,
+                joint_type,
+            });
+        }
+        let nv = joint_nv(joint_type);
+        velocity.write_qvel(&mut self.qvel_mut()[dof_addr..dof_addr + nv]);
+        Ok(())
+    }
 
-        for i in 0..self.model.ngeom() {
-            let entry = Quaternion::<f64>::new(
-                raw_quat[i * 4],
-                raw_quat[i * 4 + 1],
-                raw_quat[i * 4 + 2],
-                raw_quat[i * 4 + 3],
-            );
-            xquat.push(entry);
+    /// Looks up a joint's id, type, and offsets into `qpos` and `qvel`
+    fn joint_layout(
+        &self,
+        joint: impl ObjectRef<JointId>,
+    ) -> Result<(JointId, JointType, usize, usize), Error> {
+        let id = self.resolve(joint)?;
+        let m = unsafe { &*self.model().ptr() };
+        let i = id.index();
+        unsafe {
+            Ok((
+                id,
+                joint_type_from(*m.jnt_type.add(i)),
+                *m.jnt_qposadr.add(i) as usize,
+                *m.jnt_dofadr.add(i) as usize,
+            ))
         }
+    }
+
+    /// Returns the force that each actuator produces in its transmission
+    pub fn actuator_forces(&self) -> &[f64] {
+        self.state.actuator_forces()
+    }
 
-        xquat
+    /// Returns the length of each actuator's transmission
+    pub fn actuator_lengths(&self) -> &[f64] {
+        self.state.actuator_lengths()
     }
 
-    /// Returns generalized positions of bodies
-    pub fn qpos(&self) -> Vec<f64> {
-        let mj_data = self.state.ptr();
-        let raw_vec = unsafe { (*mj_data).qpos };
+    /// Returns the velocity of each actuator's transmission
+    pub fn actuator_velocities(&self) -> &[f64] {
+        self.state.actuator_velocities()
+    }
+
+    /// Returns the force that an actuator, looked up by name or id, produces
+    pub fn actuator_force(
+        &self,
+        actuator: impl ObjectRef<ActuatorId>,
+    ) -> Result<f64, Error> {
+        let id = self.resolve(actuator)?;
+        Ok(self.actuator_forces()[id.index()])
+    }
 
-        let mut qpos: Vec<f64> = Vec::new();
+    /// Returns the length of an actuator's transmission
+    pub fn actuator_length(
+        &self,
+        actuator: impl ObjectRef<ActuatorId>,
+    ) -> Result<f64, Error> {
+        let id = self.resolve(actuator)?;
+        Ok(self.actuator_lengths()[id.index()])
+    }
 
-        for i in 0..self.model.nq() {
-            let entry = unsafe { *raw_vec.add(i) };
-            qpos.push(entry);
+    /// Returns the velocity of an actuator's transmission
+    pub fn actuator_velocity(
+        &self,
+        actuator: impl ObjectRef<ActuatorId>,
+    ) -> Result<f64, Error> {
+        let id = self.resolve(actuator)?;
+        Ok(self.actuator_velocities()[id.index()])
+    }
+
+    /// Returns the activations of an actuator, which are empty unless it has
+    /// dynamics
+    pub fn actuator_act(
+        &self,
+        actuator: impl ObjectRef<ActuatorId>,
+    ) -> Result<&[f64], Error> {
+        let i = self.resolve(actuator)?.index();
+        let m = unsafe { &*self.model().ptr() };
+        let (adr, num) =
+            unsafe { (*m.actuator_actadr.add(i), *m.actuator_actnum.add(i)) };
+        if adr < 0 {
+            return Ok(&[]);
         }
+        let adr = adr as usize;
+        Ok(&self.act()[adr..adr + num as usize])
+    }
 
-        qpos
+    /// Returns the values of a sensor, looked up by name or id
+    pub fn sensor_data(
+        &self,
+        sensor: impl ObjectRef<SensorId>,
+    ) -> Result<&[f64], Error> {
+        let i = self.resolve(sensor)?.index();
+        let m = unsafe { &*self.model().ptr() };
+        let (adr, dim) = unsafe { (*m.sensor_adr.add(i), *m.sensor_dim.add(i)) };
+        let adr = adr as usize;
+        Ok(&self.sensordata()[adr..adr + dim as usize])
     }
 
-    /// Returns generalized velocities of bodies
-    pub fn qvel(&self) -> Vec<f64> {
-        let mj_data = self.state.ptr();
-        let raw_vec = unsafe { (*mj_data).qvel };
+    /// Returns the reading of a sensor, looked up by name or id, as a scalar,
+    /// vector or quaternion as appropriate
+    ///
+    /// ```ignore
+    /// let angle = sim.sensor("angle")?.as_scalar();
+    /// ```
+    pub fn sensor(
+        &self,
+        sensor: impl ObjectRef<SensorId>,
+    ) -> Result<SensorValue<'_>, Error> {
+        let id = self.resolve(sensor)?;
+        let data_type =
+            unsafe { *(*self.model().ptr()).sensor_datatype.add(id.index()) };
+        let values = self.sensor_data(id)?;
+        Ok(SensorValue::new(sensor_data_type_from(data_type), values))
+    }
 
-        let mut qvel: Vec<f64> = Vec::new();
+    /// Returns the number of contacts detected in the last step
+    pub fn ncon(&self) -> usize {
+        unsafe { (*self.state.ptr).ncon as usize }
+    }
 
-        for i in 0..self.model.nv() {
-            let entry = unsafe { *raw_vec.add(i) };
-            qvel.push(entry);
-        }
+    /// Iterates over the contacts detected in the last step
+    pub fn contacts(&self) -> impl Iterator<Item = Contact> + '_ {
+        let contacts = unsafe { borrow_slice((*self.state.ptr).contact, self.ncon()) };
+        let m = unsafe { &*self.model().ptr() };
+        contacts
+            .iter()
+            .enumerate()
+            .map(move |(i, contact)| unsafe { Contact::new(m, i, contact) })
+    }
 
-        qvel
+    /// Iterates over the contacts between two bodies, looked up by name or id
+    pub fn contacts_between_bodies(
+        &self,
+        a: impl ObjectRef<BodyId>,
+        b: impl ObjectRef<BodyId>,
+    ) -> Result<impl Iterator<Item = Contact> + '_, Error> {
+        let a = self.resolve(a)?;
+        let b = self.resolve(b)?;
+        Ok(self.contacts().filter(move |c| c.is_between_bodies(a, b)))
     }
 
-    /// Returns contracts
-    pub fn cfrc_ext(&self) -> Vec<Vector6<f64>> {
-        let mj_data = self.state.ptr();
-        let raw_vec = unsafe { (*mj_data).cfrc_ext };
-        let raw_quat: Vec<f64> =
-            extract_vector_float(raw_vec as *mut Local<f64>, 6, self.model.nbody())
-                .iter()
-                .map(|e| e.to_f64())
-                .collect();
-        let mut cfrc_ext: Vec<Vector6<f64>> = Vec::new();
+    /// Iterates over the contacts between two geoms, looked up by name or id
+    pub fn contacts_between_geoms(
+        &self,
+        a: impl ObjectRef<GeomId>,
+        b: impl ObjectRef<GeomId>,
+    ) -> Result<impl Iterator<Item = Contact> + '_, Error> {
+        let a = self.resolve(a)?;
+        let b = self.resolve(b)?;
+        Ok(self.contacts().filter(move |c| c.is_between_geoms(a, b)))
+    }
+
+    /// Iterates over the contacts that involve a body, looked up by name or id
+    pub fn contacts_with_body(
+        &self,
+        body: impl ObjectRef<BodyId>,
+    ) -> Result<impl Iterator<Item = Contact> + '_, Error> {
+        let body = self.resolve(body)?;
+        Ok(self.contacts().filter(move |c| c.involves_body(body)))
+    }
 
-        for i in 0..self.model.nbody() {
-            let entry = Vector6::<f64>::new(
-                raw_quat[i * 6],
-                raw_quat[i * 6 + 1],
-                raw_quat[i * 6 + 2],
-                raw_quat[i * 6 + 3],
-                raw_quat[i * 6 + 4],
-                raw_quat[i * 6 + 5],
-            );
-            cfrc_ext.push(entry);
+    /// Iterates over the contacts that involve a geom, looked up by name or id
+    pub fn contacts_with_geom(
+        &self,
+        geom: impl ObjectRef<GeomId>,
+    ) -> Result<impl Iterator<Item = Contact> + '_, Error> {
+        let geom = self.resolve(geom)?;
+        Ok(self.contacts().filter(move |c| c.involves_geom(geom)))
+    }
+
+    /// Computes the wrench that a contact exerts, in its contact frame, as
+    /// `force, torque` with the normal component first. Returns `None` if the
+    /// contact is no longer in the list of contacts, which is rebuilt by every
+    /// step, so that its index now refers to a different contact or none at all.
+    ///
+    /// Use [`Contact::frame`] to rotate the wrench into the world frame:
+    /// `contact.frame.transpose() * wrench.fixed_rows::<3>(0)`.
+    pub fn contact_force(&self, contact: &Contact) -> Option<Vector6<f64>> {
+        let contacts = unsafe { borrow_slice((*self.state.ptr).contact, self.ncon()) };
+        let current = contacts.get(contact.index)?;
+        if current.geom1 as usize != contact.geom1.index()
+            || current.geom2 as usize != contact.geom2.index()
+            || Vector3::from(current.pos) != contact.pos
+        {
+            return None;
         }
+        let mut wrench = Vector6::zeros();
+        unsafe {
+            mujoco_rs_sys::no_render::mj_contactForce(
+                self.model().ptr(),
+                self.state.ptr(),
+                contact.index as std::os::raw::c_int,
+                wrench.as_mut_ptr(),
+            )
+        };
+        Some(wrench)
+    }
 
-        cfrc_ext
+    /// Looks up the id of an object, failing if there is no such object
+    pub(crate) fn resolve<I: ObjectId>(
+        &self,
+        obj: impl ObjectRef<I>,
+    ) -> Result<I, Error> {
+        obj.resolve(self.model())
+            .ok_or_else(|| Error::NoSuchObject(obj.to_string()))
     }
 
-    /// Read sensor data
-    pub fn sensordata(&self) -> Vec<f64> {
-        let mj_data = self.state.ptr();
-        let raw_vec = unsafe { (*mj_data).sensordata };
-        let mut sensordata: Vec<f64> = Vec::new();
+    /// Returns the wrenches applied to the bodies in the world frame, as
+    /// `force, torque` at each body's center of mass, one column per body
+    pub fn xfrc_applied(&self) -> MatrixView6xX<'_, f64> {
+        self.state.xfrc_applied()
+    }
+
+    /// Mutable counterpart of [`Simulation::xfrc_applied()`]
+    pub fn xfrc_applied_mut(&mut self) -> MatrixViewMut6xX<'_, f64> {
+        self.state.xfrc_applied_mut()
+    }
+
+    /// Returns the generalized forces applied to the degrees of freedom
+    pub fn qfrc_applied(&self) -> &[f64] {
+        self.state.qfrc_applied()
+    }
+
+    /// Returns the bias forces: the Coriolis, centrifugal and gravitational
+    /// forces that are subtracted to find the accelerations
+    pub fn qfrc_bias(&self) -> &[f64] {
+        self.state.qfrc_bias()
+    }
+
+    /// Returns the passive forces from springs, dampers and fluid drag
+    pub fn qfrc_passive(&self) -> &[f64] {
+        self.state.qfrc_passive()
+    }
+
+    /// Returns the forces produced by the actuators, as generalized forces
+    pub fn qfrc_actuator(&self) -> &[f64] {
+        self.state.qfrc_actuator()
+    }
+
+    /// Returns the forces from contacts, limits and other constraints, as
+    /// generalized forces
+    pub fn qfrc_constraint(&self) -> &[f64] {
+        self.state.qfrc_constraint()
+    }
+
+    /// Mutable counterpart of [`Simulation::qfrc_applied()`]
+    pub fn qfrc_applied_mut(&mut self) -> &mut [f64] {
+        self.state.qfrc_applied_mut()
+    }
+
+    /// Applies `force` and `torque`, in the world frame, at the world `point`
+    /// on a body looked up by name or id.
+    ///
+    /// The load is converted into generalized forces and added to
+    /// [`Simulation::qfrc_applied()`], so it stays in effect, and accumulates
+    /// with further calls, until [`Simulation::clear_applied_loads()`].
+    pub fn apply_force(
+        &mut self,
+        body: impl ObjectRef<BodyId>,
+        force: Vector3<f64>,
+        torque: Vector3<f64>,
+        point: Vector3<f64>,
+    ) -> Result<(), Error> {
+        let body = self.resolve(body)?;
+        unsafe {
+            mujoco_rs_sys::no_render::mj_applyFT(
+                self.model().ptr(),
+                self.state.ptr(),
+                force.as_ptr(),
+                torque.as_ptr(),
+                point.as_ptr(),
+                body.index() as std::os::raw::c_int,
+                (*self.state.ptr).qfrc_applied,
+            )
+        };
+        Ok(())
+    }
+
+    /// Sets the wrench applied at the center of mass of a body, looked up by
+    /// name or id, with `force` and `torque` in the world frame. Replaces any
+    /// wrench that was previously set on the body.
+    pub fn set_body_wrench(
+        &mut self,
+        body: impl ObjectRef<BodyId>,
+        force: Vector3<f64>,
+        torque: Vector3<f64>,
+    ) -> Result<(), Error> {
+        let body = self.resolve(body)?;
+        let mut xfrc_applied = self.xfrc_applied_mut();
+        let mut wrench = xfrc_applied.column_mut(body.index());
+        wrench.fixed_rows_mut::<3>(0).copy_from(&force);
+        wrench.fixed_rows_mut::<3>(3).copy_from(&torque);
+        Ok(())
+    }
 
-        for i in 0..self.model.nsensordata() {
-            sensordata.push(unsafe { *raw_vec.add(i) });
+    /// Removes the wrench set on a body with [`Simulation::set_body_wrench()`]
+    pub fn clear_body_wrench(
+        &mut self,
+        body: impl ObjectRef<BodyId>,
+    ) -> Result<(), Error> {
+        self.set_body_wrench(body, Vector3::zeros(), Vector3::zeros())
+    }
+
+    /// Sets the generalized forces applied to the degrees of freedom of a
+    /// joint, looked up by name or id. `force` must have one entry per degree
+    /// of freedom.
+    pub fn set_joint_force(
+        &mut self,
+        joint: impl ObjectRef<JointId>,
+        force: &[f64],
+    ) -> Result<(), Error> {
+        let (id, joint_type, _, dof_addr) = self.joint_layout(joint)?;
+        let nv = joint_nv(joint_type);
+        if force.len() != nv {
+            return Err(Error::DofLength {
+                joint: Some(id),
+                expected: nv,
+                actual: force.len(),
+            });
         }
+        self.qfrc_applied_mut()[dof_addr..dof_addr + nv].copy_from_slice(force);
+        Ok(())
+    }
 
-        sensordata
+    /// Zeroes all applied wrenches and generalized forces. Call this after each
+    /// step to apply loads for a single step only.
+    pub fn clear_applied_loads(&mut self) {
+        self.xfrc_applied_mut().fill(0.0);
+        self.qfrc_applied_mut().iter_mut().for_each(|f| *f = 0.0);
+    }
+
+    /// Returns the external contact forces on the bodies as `torque, force`
+    /// wrenches in their center of mass frames, one column per body
+    pub fn cfrc_ext(&self) -> MatrixView6xX<'_, f64> {
+        self.state.cfrc_ext()
+    }
+
+    /// Read sensor data
+    pub fn sensordata(&self) -> &[f64] {
+        self.state.sensordata()
+    }
+
+    /// Borrows an array of the [`State`] for as long as `self` is borrowed
+    fn data_slice(&self, ptr: *const f64, len: usize) -> &[f64] {
+        unsafe { borrow_slice(ptr, len) }
     }
 
     /// Resets the state to the default values
     pub fn reset(&mut self) {
         unsafe {
-            mujoco_rs_sys::no_render::mj_resetData(self.model.ptr(), self.state.ptr())
+            mujoco_rs_sys::no_render::mj_resetData(self.model().ptr(), self.state.ptr())
+        }
+    }
+
+    /// Resets the state to the default values, and then to the values stored in
+    /// a keyframe of the model
+    pub fn reset_to_keyframe(
+        &mut self,
+        key: impl ObjectRef<KeyId>,
+    ) -> Result<(), Error> {
+        let key = self.resolve(key)?;
+        unsafe {
+            mujoco_rs_sys::no_render::mj_resetDataKeyframe(
+                self.model().ptr(),
+                self.state.ptr(),
+                key.index() as std::os::raw::c_int,
+            )
+        }
+        Ok(())
+    }
+
+    /// Captures the current time, positions, velocities, activations, controls
+    /// and mocap poses as a keyframe named `name`. See
+    /// [`Model::to_xml_string_with_keyframes()`] to add it to a model.
+    pub fn capture_keyframe(&self, name: &str) -> Keyframe {
+        unsafe { Keyframe::from_data(&*self.model().ptr(), &*self.state.ptr(), name) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::SIMPLE_XML;
+
+    #[test]
+    fn views() {
+        let mut sim = Simulation::new(Model::from_xml_str(*SIMPLE_XML).unwrap());
+        assert_eq!(sim.qpos().len(), 7);
+        assert_eq!(sim.qvel().len(), 6);
+        assert!(sim.ctrl().is_empty());
+        assert!(sim.sensordata().is_empty());
+
+        // One column per body, including the world
+        assert_eq!(sim.xpos().shape(), (3, 2));
+        assert_eq!(sim.xquat().shape(), (4, 2));
+        assert_eq!(sim.cfrc_ext().shape(), (6, 2));
+        assert_eq!(sim.xpos()[(2, 1)], 1.0);
+        assert_eq!(sim.xquat().column(1)[0], 1.0);
+
+        // Writes go straight to the underlying data
+        sim.qpos_mut()[2] = 2.0;
+        sim.qvel_mut()[0] = 0.5;
+        assert_eq!(sim.qpos()[2], 2.0);
+        assert_eq!(sim.qvel()[0], 0.5);
+        sim.step();
+        assert!(sim.qpos()[0] > 0.0);
+    }
+
+    #[test]
+    fn control() {
+        let xml = r#"<mujoco>
+    <worldbody>
+        <body>
+            <joint name="slide" type="slide"/>
+            <geom type="sphere" size=".1"/>
+        </body>
+    </worldbody>
+    <actuator>
+        <motor name="limited" joint="slide" ctrlrange="-1 1"/>
+        <motor name="free" joint="slide"/>
+    </actuator>
+</mujoco>"#;
+        let mut sim = Simulation::new(Model::from_xml_str(xml).unwrap());
+
+        sim.control(&[0.5, 2.0]).unwrap();
+        assert_eq!(sim.ctrl(), &[0.5, 2.0]);
+        assert_eq!(
+            sim.control(&[0.5]),
+            Err(Error::ControlLength {
+                expected: 2,
+                actual: 1
+            })
+        );
+        assert_eq!(
+            sim.control(&[0.0, f64::NAN]),
+            Err(Error::NonFiniteControl(ActuatorId::new(1)))
+        );
+        // Invalid input leaves the controls untouched
+        assert_eq!(sim.ctrl(), &[0.5, 2.0]);
+
+        sim.set_clamp_controls(true);
+        sim.control(&[5.0, 5.0]).unwrap();
+        assert_eq!(sim.ctrl(), &[1.0, 5.0]);
+        sim.control_actuator("limited", -3.0).unwrap();
+        sim.control_actuator(ActuatorId::new(1), -3.0).unwrap();
+        assert_eq!(sim.ctrl(), &[-1.0, -3.0]);
+
+        assert_eq!(
+            sim.control_actuator("missing", 0.0),
+            Err(Error::NoSuchObject("missing".to_owned()))
+        );
+        assert!(sim.control_actuator(ActuatorId::new(2), 0.0).is_err());
+        assert_eq!(
+            sim.control_actuator("free", f64::INFINITY),
+            Err(Error::NonFiniteControl(ActuatorId::new(1)))
+        );
+    }
+
+    #[test]
+    fn actuator_readouts() {
+        let xml = r#"<mujoco>
+    <worldbody>
+        <body>
+            <joint name="slide" type="slide"/>
+            <geom type="sphere" size=".1"/>
+        </body>
+    </worldbody>
+    <actuator>
+        <motor name="motor" joint="slide" gear="2"/>
+        <general name="filtered" joint="slide" dyntype="filter" dynprm="1"/>
+    </actuator>
+</mujoco>"#;
+        let mut sim = Simulation::new(Model::from_xml_str(xml).unwrap());
+        sim.qpos_mut()[0] = 0.25;
+        sim.qvel_mut()[0] = 0.5;
+        sim.control(&[1.5, 1.0]).unwrap();
+        sim.step();
+
+        assert_eq!(sim.actuator_forces().len(), 2);
+        // The force is in actuator space, before the gear is applied
+        assert_eq!(sim.actuator_force("motor"), Ok(1.5));
+        assert_eq!(sim.actuator_force(ActuatorId::new(0)), Ok(1.5));
+        assert_eq!(sim.actuator_length("motor"), Ok(0.5));
+        assert_eq!(sim.actuator_velocity("motor"), Ok(1.0));
+        assert_eq!(
+            sim.actuator_force("missing"),
+            Err(Error::NoSuchObject("missing".to_owned()))
+        );
+
+        assert_eq!(sim.actuator_act("motor"), Ok(&[][..]));
+        let act = sim.actuator_act("filtered").unwrap();
+        assert_eq!(act.len(), 1);
+        assert!(act[0] > 0.0 && act[0] < 1.0);
+    }
+
+    #[test]
+    fn joint_positions_and_velocities() {
+        let xml = r#"<mujoco>
+    <worldbody>
+        <body name="base">
+            <freejoint name="root"/>
+            <geom type="sphere" size=".1"/>
+            <body name="arm">
+                <joint name="shoulder" type="ball"/>
+                <joint name="elbow" type="hinge"/>
+                <geom type="capsule" size=".05" fromto="0 0 0 .5 0 0"/>
+            </body>
+        </body>
+    </worldbody>
+</mujoco>"#;
+        let mut sim = Simulation::new(Model::from_xml_str(xml).unwrap());
+
+        assert_eq!(sim.joint_qpos("root").unwrap().len(), 7);
+        assert_eq!(sim.joint_qvel("shoulder").unwrap().len(), 3);
+        assert_eq!(
+            sim.joint_position("shoulder"),
+            Ok(JointPosition::Ball(UnitQuaternion::identity()))
+        );
+
+        let quat = UnitQuaternion::from_euler_angles(0.0, 0.0, 0.5);
+        sim.set_joint_position("shoulder", JointPosition::Ball(quat))
+            .unwrap();
+        sim.set_joint_position(JointId::new(2), JointPosition::Hinge(0.25))
+            .unwrap();
+        let shoulder = sim.joint_qpos("shoulder").unwrap();
+        assert_eq!(shoulder, &[quat.w, quat.i, quat.j, quat.k]);
+        assert_eq!(sim.qpos()[11], 0.25);
+
+        sim.joint_qpos_mut("root").unwrap()[2] = 1.0;
+        match sim.joint_position("root").unwrap() {
+            JointPosition::Free { pos, quat } => {
+                assert_eq!(pos, Vector3::new(0.0, 0.0, 1.0));
+                assert_eq!(quat, UnitQuaternion::identity());
+            }
+            p => panic!("Unexpected position: {:?}", p),
+        }
+
+        let velocity = JointVelocity::Free {
+            linear: Vector3::x(),
+            angular: Vector3::z(),
+        };
+        sim.set_joint_velocity("root", velocity).unwrap();
+        assert_eq!(&sim.qvel()[..6], &[1.0, 0.0, 0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(sim.joint_velocity("root"), Ok(velocity));
+        sim.joint_qvel_mut("elbow").unwrap()[0] = -2.0;
+        assert_eq!(sim.joint_velocity("elbow"), Ok(JointVelocity::Hinge(-2.0)));
+
+        assert_eq!(
+            sim.set_joint_position("elbow", JointPosition::Slide(0.0)),
+            Err(Error::JointTypeMismatch {
+                joint: JointId::new(2),
+                joint_type: JointType::HINGE,
+            })
+        );
+        assert_eq!(
+            sim.set_joint_velocity("missing", JointVelocity::Slide(0.0)),
+            Err(Error::NoSuchObject("missing".to_owned()))
+        );
+        assert_eq!(
+            sim.joint_qpos("missing"),
+            Err(Error::NoSuchObject("missing".to_owned()))
+        );
+    }
+
+    #[test]
+    fn sensors() {
+        let xml = r#"<mujoco>
+    <worldbody>
+        <body name="arm" pos="0 0 1">
+            <joint name="hinge" type="hinge" axis="0 0 1"/>
+            <geom type="capsule" size=".05" fromto="0 0 0 .5 0 0"/>
+            <site name="tip" pos=".5 0 0"/>
+        </body>
+    </worldbody>
+    <sensor>
+        <jointpos name="angle" joint="hinge"/>
+        <framepos name="position" objtype="site" objname="tip"/>
+        <framequat name="orientation" objtype="site" objname="tip"/>
+    </sensor>
+</mujoco>"#;
+        let mut sim = Simulation::new(Model::from_xml_str(xml).unwrap());
+        sim.qpos_mut()[0] = std::f64::consts::FRAC_PI_2;
+        sim.step();
+
+        let angle = sim.sensor("angle").unwrap().as_scalar().unwrap();
+        assert_eq!(angle, std::f64::consts::FRAC_PI_2);
+        assert_eq!(sim.sensor(SensorId::new(0)), Ok(SensorValue::Scalar(angle)));
+
+        let position = sim.sensor("position").unwrap().as_vector3().unwrap();
+        assert!((position - Vector3::new(0.0, 0.5, 1.0)).norm() < 1e-9);
+
+        let orientation = sim.sensor("orientation").unwrap().as_quaternion().unwrap();
+        let expected = UnitQuaternion::from_euler_angles(0.0, 0.0, angle);
+        assert!((orientation - *expected).norm() < 1e-9);
+
+        assert_eq!(sim.sensor_data("orientation").unwrap().len(), 4);
+        assert_eq!(sim.sensordata().len(), 8);
+        assert_eq!(
+            sim.sensor("missing"),
+            Err(Error::NoSuchObject("missing".to_owned()))
+        );
+    }
+
+    #[test]
+    fn contacts() {
+        let xml = r#"<mujoco>
+    <worldbody>
+        <geom name="floor" type="plane" size="1 1 0.1"/>
+        <body name="ball" pos="0 0 .09">
+            <freejoint/>
+            <geom name="ball" type="sphere" size=".1" mass="1"/>
+        </body>
+        <body name="box" pos=".5 0 .3">
+            <freejoint/>
+            <geom name="box" type="box" size=".1 .1 .1"/>
+        </body>
+    </worldbody>
+</mujoco>"#;
+        let mut sim = Simulation::new(Model::from_xml_str(xml).unwrap());
+        sim.step();
+
+        // Only the ball is touching the floor
+        assert_eq!(sim.ncon(), 1);
+        let contact = sim.contacts().next().unwrap();
+        let floor: GeomId = sim.model().name_to_id("floor").unwrap();
+        let ball: BodyId = sim.model().name_to_id("ball").unwrap();
+        let box_body: BodyId = sim.model().name_to_id("box").unwrap();
+        assert!(contact.is_between_geoms(floor, GeomId::new(1)));
+        assert!(contact.is_between_bodies(BodyId::WORLD, ball));
+        assert!(contact.dist < 0.0);
+        assert_eq!(contact.dim, 3);
+        assert!(contact.efc_address.is_some());
+        assert!((contact.frame.row(0) - Vector3::z().transpose()).norm() < 1e-9);
+
+        // The floor pushes the ball up
+        let wrench = sim.contact_force(&contact).unwrap();
+        assert!(wrench[0] > 0.0);
+
+        assert_eq!(
+            sim.contacts_between_bodies("world", "ball")
+                .unwrap()
+                .count(),
+            1
+        );
+        assert_eq!(
+            sim.contacts_between_bodies("ball", "world")
+                .unwrap()
+                .count(),
+            1
+        );
+        assert_eq!(
+            sim.contacts_between_bodies("world", "box").unwrap().count(),
+            0
+        );
+        assert_eq!(
+            sim.contacts_between_geoms("ball", "floor").unwrap().count(),
+            1
+        );
+        assert_eq!(sim.contacts_with_body("box").unwrap().count(), 0);
+        assert_eq!(sim.contacts_with_geom(floor).unwrap().count(), 1);
+        assert!(sim.contacts_with_body("missing").is_err());
+
+        let stale = Contact {
+            index: 5,
+            ..contact
+        };
+        assert_eq!(sim.contact_force(&stale), None);
+        let moved = Contact {
+            pos: contact.pos + Vector3::x(),
+            ..contact
+        };
+        assert_eq!(sim.contact_force(&moved), None);
+
+        // After lifting the ball and dropping the box onto the floor, the first
+        // contact is between other geoms
+        sim.qpos_mut()[2] = 1.0;
+        sim.qpos_mut()[9] = 0.09;
+        sim.step();
+        assert!(sim.ncon() > 0);
+        assert!(sim
+            .contacts()
+            .all(|c| c.is_between_bodies(BodyId::WORLD, box_body)));
+        assert_eq!(sim.contact_force(&contact), None);
+        let current = sim.contacts().next().unwrap();
+        assert!(sim.contact_force(&current).is_some());
+    }
+
+    #[test]
+    fn applied_loads() {
+        let xml = r#"<mujoco>
+    <option gravity="0 0 0"/>
+    <worldbody>
+        <body name="ball">
+            <freejoint name="root"/>
+            <geom type="sphere" size=".1" mass="1"/>
+        </body>
+        <body name="arm" pos="1 0 0">
+            <joint name="hinge" type="hinge" axis="0 0 1"/>
+            <geom type="capsule" size=".05" fromto="0 0 0 .5 0 0"/>
+        </body>
+    </worldbody>
+</mujoco>"#;
+        let mut sim = Simulation::new(Model::from_xml_str(xml).unwrap());
+
+        // A force through the center of mass only pushes the ball
+        sim.apply_force("ball", Vector3::x(), Vector3::zeros(), Vector3::zeros())
+            .unwrap();
+        assert_eq!(&sim.qfrc_applied()[..6], &[1.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        // Off center, it also makes the ball spin
+        sim.apply_force("ball", Vector3::x(), Vector3::zeros(), Vector3::y())
+            .unwrap();
+        assert_eq!(sim.qfrc_applied()[0], 2.0);
+        assert!(sim.qfrc_applied()[5] < 0.0);
+
+        sim.set_body_wrench("arm", Vector3::z(), Vector3::x())
+            .unwrap();
+        let arm: BodyId = sim.model().name_to_id("arm").unwrap();
+        assert_eq!(
+            sim.xfrc_applied().column(arm.index()).as_slice(),
+            &[0.0, 0.0, 1.0, 1.0, 0.0, 0.0]
+        );
+        sim.clear_body_wrench(arm).unwrap();
+        assert_eq!(sim.xfrc_applied().column(arm.index()).sum(), 0.0);
+
+        sim.set_joint_force("hinge", &[0.5]).unwrap();
+        assert_eq!(sim.qfrc_applied()[6], 0.5);
+        assert_eq!(
+            sim.set_joint_force("root", &[0.5]),
+            Err(Error::DofLength {
+                joint: Some(JointId::new(0)),
+                expected: 6,
+                actual: 1
+            })
+        );
+
+        sim.step();
+        assert!(sim.qvel()[0] > 0.0);
+        assert!(sim.qvel()[6] > 0.0);
+
+        sim.set_body_wrench("ball", Vector3::x(), Vector3::zeros())
+            .unwrap();
+        sim.clear_applied_loads();
+        assert!(sim.qfrc_applied().iter().all(|&f| f == 0.0));
+        assert!(sim.xfrc_applied().iter().all(|&f| f == 0.0));
+        assert!(sim
+            .apply_force("missing", Vector3::x(), Vector3::zeros(), Vector3::zeros())
+            .is_err());
+    }
+
+    #[test]
+    fn poses() {
+        // More geoms than bodies, so that per-body and per-geom arrays differ
+        let xml = r#"<mujoco>
+    <worldbody>
+        <geom name="floor" type="plane" size="1 1 0.1"/>
+        <camera name="cam" pos="0 -2 1" xyaxes="1 0 0 0 0 1"/>
+        <body name="arm" pos="0 0 1" euler="0 0 90">
+            <geom name="upper" type="capsule" size=".05" fromto="0 0 0 .5 0 0"/>
+            <geom name="lower" type="sphere" size=".1" pos=".5 0 0" mass="5"/>
+            <site name="tip" pos=".6 0 0"/>
+        </body>
+    </worldbody>
+</mujoco>"#;
+        let sim = Simulation::new(Model::from_xml_str(xml).unwrap());
+        let eps = 1e-9;
+        assert_eq!(sim.xpos().ncols(), 2);
+        assert_eq!(sim.xquat().ncols(), 2);
+
+        let arm = sim.body_pose("arm").unwrap();
+        assert!((arm.translation.vector - Vector3::new(0.0, 0.0, 1.0)).norm() < eps);
+        let x_axis = arm.rotation * Vector3::x();
+        assert!((x_axis - Vector3::y()).norm() < eps);
+
+        // The same object can be looked up by name or by id
+        let lower = sim.geom_pose("lower").unwrap();
+        let lower_id: GeomId = sim.model().name_to_id("lower").unwrap();
+        assert_eq!(sim.geom_pose(lower_id), Ok(lower));
+        assert!((lower.translation.vector - Vector3::new(0.0, 0.5, 1.0)).norm() < eps);
+
+        let tip = sim.site_pose("tip").unwrap();
+        assert!((tip.translation.vector - Vector3::new(0.0, 0.6, 1.0)).norm() < eps);
+
+        // The heavy sphere pulls the center of mass towards it
+        let com = sim.body_inertial_pose(BodyId::new(1)).unwrap();
+        assert!(com.translation.vector.y > 0.3);
+
+        let cam = sim.camera_pose("cam").unwrap();
+        let forward = cam.rotation * -Vector3::z();
+        assert!((forward - Vector3::y()).norm() < eps);
+
+        assert_eq!(
+            sim.geom_pose("missing"),
+            Err(Error::NoSuchObject("missing".to_owned()))
+        );
+        assert_eq!(
+            sim.geom_pose(GeomId::new(3)),
+            Err(Error::NoSuchObject("GeomId(3)".to_owned()))
+        );
+        assert_eq!(
+            sim.site_pose(SiteId::new(1)),
+            Err(Error::NoSuchObject("SiteId(1)".to_owned()))
+        );
+    }
+
+    #[test]
+    fn split_step() {
+        let model = Arc::new(Model::from_xml_str(*SIMPLE_XML).unwrap());
+        let mut whole = Simulation::new(model.clone());
+        let mut split = Simulation::new(model);
+        for _ in 0..10 {
+            whole.step();
+            split.step1();
+            split.step2();
+        }
+        assert_eq!(split.state().time(), whole.state().time());
+        assert_eq!(split.qpos(), whole.qpos());
+        assert_eq!(split.qvel(), whole.qvel());
+    }
+
+    #[test]
+    fn stages() {
+        let mut sim = Simulation::new(Model::from_xml_str(*SIMPLE_XML).unwrap());
+        let eps = 1e-9;
+
+        // Poses only follow `qpos` once kinematics have been computed
+        sim.qpos_mut()[0] = 0.5;
+        assert_eq!(sim.body_pose("body1").unwrap().translation.x, 0.0);
+        sim.kinematics();
+        assert_eq!(sim.body_pose("body1").unwrap().translation.x, 0.5);
+
+        // The body is in free fall, so only gravity acts on it
+        sim.fwd_position();
+        sim.forward_skip(Stage::POS, false);
+        assert!((sim.qacc()[2] + 9.81).abs() < eps);
+        sim.forward();
+        assert!((sim.qacc()[2] + 9.81).abs() < eps);
+
+        // Falling under gravity alone needs no extra force
+        sim.inverse();
+        assert!(sim.qfrc_inverse().iter().all(|f| f.abs() < eps));
+
+        // Holding the body in place takes its weight
+        sim.qacc_mut().iter_mut().for_each(|a| *a = 0.0);
+        sim.inverse_skip(Stage::VEL, true);
+        let mass = unsafe { *(*sim.model().ptr()).body_mass.add(1) };
+        assert!((sim.qfrc_inverse()[2] - mass * 9.81).abs() < 1e-6);
+        assert_eq!(sim.state().time(), 0.0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_values() {
+        let xml = r#"<mujoco>
+    <worldbody>
+        <geom name="floor" type="plane" size="1 1 0.1"/>
+        <body name="ball" pos="0 0 .09">
+            <joint name="root" type="free"/>
+            <geom name="ball" type="sphere" size=".1" mass="1"/>
+        </body>
+        <body name="arm" pos="0 0 1">
+            <joint name="hinge" type="hinge"/>
+            <geom type="capsule" size=".05" fromto="0 0 0 .5 0 0"/>
+        </body>
+    </worldbody>
+</mujoco>"#;
+        let mut sim = Simulation::new(Model::from_xml_str(xml).unwrap());
+        sim.qvel_mut()[6] = 2.0;
+        sim.step();
+
+        let contacts: Vec<Contact> = sim.contacts().collect();
+        assert!(!contacts.is_empty());
+        let json = serde_json::to_string(&contacts).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<Contact>>(&json).unwrap(),
+            contacts
+        );
+
+        for joint in ["root", "hinge"] {
+            let position = sim.joint_position(joint).unwrap();
+            let json = serde_json::to_string(&position).unwrap();
+            let decoded: JointPosition = serde_json::from_str(&json).unwrap();
+            assert_eq!(decoded, position);
+
+            let velocity = sim.joint_velocity(joint).unwrap();
+            let json = serde_json::to_string(&velocity).unwrap();
+            let decoded: JointVelocity = serde_json::from_str(&json).unwrap();
+            assert_eq!(decoded, velocity);
         }
     }
 }
@@ -0,0 +1,347 @@
+//! Owned copies of parts of a [`State`](crate::State), for branching and
+//! replaying simulations.
+//!
+//! This mirrors `mj_getState`/`mj_setState` and the `mjtState` bits of newer
+//! versions of MuJoCo, which 2.3.5 does not have yet. The layout of a
+//! [`StateSnapshot`] is the same as theirs: the selected components,
+//! concatenated in the order of the bits.
+
+use std::ops::{BitAnd, BitOr, BitOrAssign};
+
+use mujoco_rs_sys::no_render::{mjData, mjModel};
+
+use crate::{Error, Model, State};
+
+/// A set of components of a [`State`](crate::State), as in `mjtState`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StateMask(pub u32);
+
+impl StateMask {
+    pub const TIME: Self = Self(1 << 0);
+    pub const QPOS: Self = Self(1 << 1);
+    pub const QVEL: Self = Self(1 << 2);
+    /// Actuator activations
+    pub const ACT: Self = Self(1 << 3);
+    /// Accelerations that warm start the constraint solver
+    pub const WARMSTART: Self = Self(1 << 4);
+    pub const CTRL: Self = Self(1 << 5);
+    pub const QFRC_APPLIED: Self = Self(1 << 6);
+    pub const XFRC_APPLIED: Self = Self(1 << 7);
+    pub const MOCAP_POS: Self = Self(1 << 8);
+    pub const MOCAP_QUAT: Self = Self(1 << 9);
+    pub const USERDATA: Self = Self(1 << 10);
+    /// The state of engine plugins
+    pub const PLUGIN: Self = Self(1 << 11);
+
+    pub const EMPTY: Self = Self(0);
+    /// Everything that evolves with the simulation on its own
+    pub const PHYSICS: Self = Self(Self::QPOS.0 | Self::QVEL.0 | Self::ACT.0);
+    /// [`StateMask::PHYSICS`] together with the time and plugin state
+    pub const FULL_PHYSICS: Self =
+        Self(Self::TIME.0 | Self::PHYSICS.0 | Self::PLUGIN.0);
+    /// Everything that the user sets as input to the simulation
+    pub const USER: Self = Self(
+        Self::CTRL.0
+            | Self::QFRC_APPLIED.0
+            | Self::XFRC_APPLIED.0
+            | Self::MOCAP_POS.0
+            | Self::MOCAP_QUAT.0
+            | Self::USERDATA.0,
+    );
+    /// Everything needed to continue a simulation bit-for-bit
+    pub const INTEGRATION: Self =
+        Self(Self::FULL_PHYSICS.0 | Self::USER.0 | Self::WARMSTART.0);
+
+    /// Every component, in the order they are laid out in a snapshot
+    const COMPONENTS: [Self; 12] = [
+        Self::TIME,
+        Self::QPOS,
+        Self::QVEL,
+        Self::ACT,
+        Self::WARMSTART,
+        Self::CTRL,
+        Self::QFRC_APPLIED,
+        Self::XFRC_APPLIED,
+        Self::MOCAP_POS,
+        Self::MOCAP_QUAT,
+        Self::USERDATA,
+        Self::PLUGIN,
+    ];
+
+    /// Whether every component in `other` is also in `self`
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Iterates over the single components in the mask, in layout order
+    fn components(self) -> impl Iterator<Item = Self> {
+        Self::COMPONENTS
+            .iter()
+            .copied()
+            .filter(move |&c| self.contains(c))
+    }
+
+    /// The number of values that the components in the mask take up for a
+    /// model `m`
+    pub(crate) fn size(self, m: &mjModel) -> usize {
+        self.components().map(|c| c.len(m)).sum()
+    }
+
+    /// The number of values of the single component `self`
+    fn len(self, m: &mjModel) -> usize {
+        let n = match self {
+            Self::TIME => 1,
+            Self::QPOS => m.nq,
+            Self::QVEL | Self::WARMSTART | Self::QFRC_APPLIED => m.nv,
+            Self::ACT => m.na,
+            Self::CTRL => m.nu,
+            Self::XFRC_APPLIED => 6 * m.nbody,
+            Self::MOCAP_POS => 3 * m.nmocap,
+            Self::MOCAP_QUAT => 4 * m.nmocap,
+            Self::USERDATA => m.nuserdata,
+            Self::PLUGIN => m.npluginstate,
+            _ => unreachable!("Not a single component"),
+        };
+        n as usize
+    }
+
+    /// Points at the values of the single component `self` in `d`
+    ///
+    /// # Safety
+    /// `d` must be a valid `mjData` made for `m`.
+    unsafe fn ptr(self, m: &mjModel, d: *mut mjData) -> (*mut f64, usize) {
+        let ptr = match self {
+            Self::TIME => &mut (*d).time as *mut f64,
+            Self::QPOS => (*d).qpos,
+            Self::QVEL => (*d).qvel,
+            Self::ACT => (*d).act,
+            Self::WARMSTART => (*d).qacc_warmstart,
+            Self::CTRL => (*d).ctrl,
+            Self::QFRC_APPLIED => (*d).qfrc_applied,
+            Self::XFRC_APPLIED => (*d).xfrc_applied,
+            Self::MOCAP_POS => (*d).mocap_pos,
+            Self::MOCAP_QUAT => (*d).mocap_quat,
+            Self::USERDATA => (*d).userdata,
+            Self::PLUGIN => (*d).plugin_state,
+            _ => unreachable!("Not a single component"),
+        };
+        (ptr, self.len(m))
+    }
+}
+
+impl BitOr for StateMask {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for StateMask {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for StateMask {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+/// An owned copy of the components of a [`State`](crate::State) selected by
+/// a [`StateMask`]. Taken with [`State::snapshot()`] and written back with
+/// [`State::restore()`].
+///
+/// [`State::snapshot()`]: crate::State::snapshot
+/// [`State::restore()`]: crate::State::restore
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StateSnapshot {
+    mask: StateMask,
+    data: Vec<f64>,
+}
+
+impl StateSnapshot {
+    /// Copies the components in `mask` out of `d`
+    ///
+    /// # Safety
+    /// `d` must be a valid `mjData` made for `m`.
+    pub(crate) unsafe fn new(m: &mjModel, d: *mut mjData, mask: StateMask) -> Self {
+        let mut data = Vec::with_capacity(mask.size(m));
+        for component in mask.components() {
+            let (ptr, len) = component.ptr(m, d);
+            if len > 0 {
+                data.extend_from_slice(std::slice::from_raw_parts(ptr, len));
+            }
+        }
+        Self { mask, data }
+    }
+
+    /// Builds a snapshot from values laid out as by `mj_getState`. See
+    /// [`StateSnapshot::as_slice()`].
+    pub fn from_raw(mask: StateMask, data: Vec<f64>) -> Self {
+        Self { mask, data }
+    }
+
+    /// Copies the snapshot into `d`
+    ///
+    /// # Safety
+    /// `d` must be a valid `mjData` made for `m`, and the snapshot must have
+    /// `self.mask.size(m)` values.
+    pub(crate) unsafe fn write(&self, m: &mjModel, d: *mut mjData) {
+        let mut values = self.data.as_slice();
+        for component in self.mask.components() {
+            let (ptr, len) = component.ptr(m, d);
+            let (head, tail) = values.split_at(len);
+            if len > 0 {
+                std::slice::from_raw_parts_mut(ptr, len).copy_from_slice(head);
+            }
+            values = tail;
+        }
+    }
+
+    /// The components that the snapshot holds
+    pub fn mask(&self) -> StateMask {
+        self.mask
+    }
+
+    /// The values of all the components, concatenated in the order of their
+    /// bits in [`StateMask`]
+    pub fn as_slice(&self) -> &[f64] {
+        &self.data
+    }
+
+    /// The simulation time, if the snapshot holds it
+    pub fn time(&self) -> Option<f64> {
+        self.component_at(StateMask::TIME, 0, 1)?.first().copied()
+    }
+
+    /// The values of a single component, if the snapshot holds it. `m` must
+    /// be the model that the snapshot was taken with.
+    pub fn component(&self, m: &crate::Model, component: StateMask) -> Option<&[f64]> {
+        if !StateMask::COMPONENTS.contains(&component) {
+            return None;
+        }
+        let m = unsafe { &*m.ptr() };
+        let start = (self.mask & StateMask(component.0 - 1)).size(m);
+        self.component_at(component, start, component.len(m))
+    }
+
+    /// The `len` values from `start` on, if the snapshot holds `component`
+    fn component_at(
+        &self,
+        component: StateMask,
+        start: usize,
+        len: usize,
+    ) -> Option<&[f64]> {
+        if !self.mask.contains(component) {
+            return None;
+        }
+        self.data.get(start..start + len)
+    }
+}
+
+/// A model together with a snapshot of a state of it, from which a simulation
+/// can be resumed exactly, such as on another machine. With the `serde`
+/// feature, it can be stored in any format that serde supports.
+///
+/// ```ignore
+/// let bytes = serde_json::to_vec(&Checkpoint::new(sim.state()))?;
+/// // ...
+/// let checkpoint: Checkpoint = serde_json::from_slice(&bytes)?;
+/// let sim = Simulation::from_state(checkpoint.restore()?);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Checkpoint {
+    model: Vec<u8>,
+    snapshot: StateSnapshot,
+}
+
+impl Checkpoint {
+    /// Captures `state` and its model, with everything in
+    /// [`StateMask::INTEGRATION`]
+    pub fn new(state: &State) -> Self {
+        Self {
+            model: state.model().to_vec(),
+            snapshot: state.snapshot(StateMask::INTEGRATION),
+        }
+    }
+
+    /// The model, in the MJB format of [`Model::to_vec()`]
+    pub fn model_bytes(&self) -> &[u8] {
+        &self.model
+    }
+
+    pub fn snapshot(&self) -> &StateSnapshot {
+        &self.snapshot
+    }
+
+    /// Loads the model and creates a [`State`] of it that continues from the
+    /// checkpoint
+    pub fn restore(&self) -> Result<State, Error> {
+        let model = Model::from_bytes(&self.model)?;
+        let mut state = State::new(model);
+        state.restore(&self.snapshot)?;
+        unsafe { mujoco_rs_sys::no_render::mj_forward(state.model.ptr, state.ptr) };
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::SIMPLE_XML;
+    use crate::Simulation;
+
+    fn stepped(n: usize) -> Simulation {
+        let mut sim = Simulation::new(Model::from_xml_str(*SIMPLE_XML).unwrap());
+        for _ in 0..n {
+            sim.step();
+        }
+        sim
+    }
+
+    #[test]
+    fn checkpoint_restore() {
+        let mut sim = stepped(10);
+        let checkpoint = Checkpoint::new(sim.state());
+        let mut resumed = Simulation::from_state(checkpoint.restore().unwrap());
+        assert_eq!(resumed.state().time(), sim.state().time());
+        assert_eq!(resumed.body_pose("body1"), sim.body_pose("body1"));
+
+        for _ in 0..10 {
+            sim.step();
+            resumed.step();
+        }
+        assert_eq!(resumed.qpos(), sim.qpos());
+    }
+
+    #[test]
+    fn invalid_checkpoint() {
+        let mut checkpoint = Checkpoint::new(stepped(0).state());
+        checkpoint.model.truncate(10);
+        assert_eq!(checkpoint.restore().unwrap_err(), Error::InvalidMjb);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let sim = stepped(10);
+        let snapshot = sim.state().snapshot(StateMask::PHYSICS | StateMask::TIME);
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let decoded: StateSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, snapshot);
+
+        let checkpoint = Checkpoint::new(sim.state());
+        let bytes = serde_json::to_vec(&checkpoint).unwrap();
+        let decoded: Checkpoint = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(decoded, checkpoint);
+        let state = decoded.restore().unwrap();
+        assert_eq!(state.time(), sim.state().time());
+    }
+}
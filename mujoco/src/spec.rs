@@ -0,0 +1,748 @@
+//! Procedural model construction.
+//!
+//! A [`Spec`] is a typed tree of bodies, joints, geoms, sites, actuators and
+//! assets that can be built and edited in Rust and then compiled into a
+//! [`Model`]. It mirrors MuJoCo's `mjSpec` editing API, which is not available
+//! in the MuJoCo 2.3 C API that this crate binds, so compilation goes through
+//! MJCF: [`Spec::to_xml()`] emits the tree as XML, and [`Spec::compile()`]
+//! hands it to MuJoCo's compiler. All angles are in radians.
+
+use std::fmt::Write;
+use std::sync::Arc;
+
+use nalgebra::{Quaternion, Vector3};
+
+use crate::joint::{joint_nq, joint_nv};
+use crate::re_exports::{GeomType, JointType};
+use crate::{ActuatorId, Error, JointId, Model, State};
+
+/// The root of a model description
+#[derive(Debug, Clone, Default)]
+pub struct Spec {
+    /// The name of the model
+    pub model_name: Option<String>,
+    /// The world body, which is the root of the kinematic tree
+    pub worldbody: BodySpec,
+    pub actuators: Vec<ActuatorSpec>,
+    pub meshes: Vec<MeshSpec>,
+    pub hfields: Vec<HFieldSpec>,
+}
+impl Spec {
+    /// Creates an empty `Spec`, with nothing but the world body
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an actuator to the `Spec` and returns it for further editing
+    pub fn add_actuator(&mut self, actuator: ActuatorSpec) -> &mut ActuatorSpec {
+        push(&mut self.actuators, actuator)
+    }
+
+    /// Adds a mesh asset to the `Spec` and returns it for further editing
+    pub fn add_mesh(&mut self, mesh: MeshSpec) -> &mut MeshSpec {
+        push(&mut self.meshes, mesh)
+    }
+
+    /// Adds a heightfield asset to the `Spec` and returns it for further editing
+    pub fn add_hfield(&mut self, hfield: HFieldSpec) -> &mut HFieldSpec {
+        push(&mut self.hfields, hfield)
+    }
+
+    /// Compiles the `Spec` into a [`Model`]
+    pub fn compile(&self) -> Result<Model, Error> {
+        Model::from_xml_str(self.to_xml()?)
+    }
+
+    /// Compiles the `Spec` into a [`Model`], and carries `state` over to a new
+    /// [`State`] for it, so that a model can be edited while it is simulated.
+    ///
+    /// The positions and velocities of joints, and the controls and
+    /// activations of actuators, are copied to the objects with the same name
+    /// in the new model. Everything else, including the time, objects that
+    /// are new or unnamed, and joints that changed type, starts from the
+    /// defaults. Quantities derived from the state, such as body poses, are
+    /// only updated by the next step or call to `mj_forward`.
+    pub fn recompile(&self, state: &State) -> Result<(Arc<Model>, State), Error> {
+        let model = Arc::new(self.compile()?);
+        let mut new_state = State::new(model.clone());
+        let old_model = state.model();
+
+        let old_joints = old_model.joints();
+        let new_joints = model.joints();
+        for (id, name) in model.names_of::<JointId>() {
+            let old = match old_model.name_to_id::<JointId>(name) {
+                Some(old) => &old_joints[old.index()],
+                None => continue,
+            };
+            let new = &new_joints[id.index()];
+            if old.joint_type != new.joint_type {
+                continue;
+            }
+            let (nq, nv) = (joint_nq(new.joint_type), joint_nv(new.joint_type));
+            new_state.qpos_mut()[new.qpos_addr..new.qpos_addr + nq]
+                .copy_from_slice(&state.qpos()[old.qpos_addr..old.qpos_addr + nq]);
+            new_state.qvel_mut()[new.dof_addr..new.dof_addr + nv]
+                .copy_from_slice(&state.qvel()[old.dof_addr..old.dof_addr + nv]);
+        }
+
+        let old_actuators = old_model.actuators();
+        let new_actuators = model.actuators();
+        for (id, name) in model.names_of::<ActuatorId>() {
+            let old = match old_model.name_to_id::<ActuatorId>(name) {
+                Some(old) => &old_actuators[old.index()],
+                None => continue,
+            };
+            let new = &new_actuators[id.index()];
+            new_state.ctrl_mut()[new.id.index()] = state.ctrl()[old.id.index()];
+            if let (Some(new_addr), Some(old_addr)) = (new.act_addr, old.act_addr) {
+                let n = new.act_num.min(old.act_num);
+                new_state.act_mut()[new_addr..new_addr + n]
+                    .copy_from_slice(&state.act()[old_addr..old_addr + n]);
+            }
+        }
+
+        Ok((model, new_state))
+    }
+
+    /// Emits the `Spec` as MJCF XML. Fails if it has a geom of a type that
+    /// can't be written in MJCF, such as [`GeomType::ARROW`].
+    pub fn to_xml(&self) -> Result<String, Error> {
+        let mut xml = String::new();
+        match &self.model_name {
+            Some(name) => writeln!(xml, "<mujoco model=\"{}\">", escape(name)),
+            None => writeln!(xml, "<mujoco>"),
+        }
+        .unwrap();
+        writeln!(xml, "  <compiler angle=\"radian\"/>").unwrap();
+
+        if !self.meshes.is_empty() || !self.hfields.is_empty() {
+            writeln!(xml, "  <asset>").unwrap();
+            for mesh in &self.meshes {
+                mesh.write_xml(&mut xml);
+            }
+            for hfield in &self.hfields {
+                hfield.write_xml(&mut xml);
+            }
+            writeln!(xml, "  </asset>").unwrap();
+        }
+
+        writeln!(xml, "  <worldbody>").unwrap();
+        self.worldbody.write_children(&mut xml, 2)?;
+        writeln!(xml, "  </worldbody>").unwrap();
+
+        if !self.actuators.is_empty() {
+            writeln!(xml, "  <actuator>").unwrap();
+            for actuator in &self.actuators {
+                actuator.write_xml(&mut xml);
+            }
+            writeln!(xml, "  </actuator>").unwrap();
+        }
+        writeln!(xml, "</mujoco>").unwrap();
+        Ok(xml)
+    }
+}
+
+/// A body in the kinematic tree, along with everything attached to it
+#[derive(Debug, Clone)]
+pub struct BodySpec {
+    pub name: Option<String>,
+    /// Position relative to the parent body
+    pub pos: Vector3<f64>,
+    /// Orientation relative to the parent body
+    pub quat: Quaternion<f64>,
+    /// Whether the body is a mocap body. Only children of the world can be.
+    pub mocap: bool,
+    pub bodies: Vec<BodySpec>,
+    pub joints: Vec<JointSpec>,
+    pub geoms: Vec<GeomSpec>,
+    pub sites: Vec<SiteSpec>,
+}
+impl BodySpec {
+    /// Creates a body at the origin of its parent, with the given name
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a child body and returns it for further editing
+    pub fn add_body(&mut self, body: BodySpec) -> &mut BodySpec {
+        push(&mut self.bodies, body)
+    }
+
+    /// Adds a joint to the body and returns it for further editing
+    pub fn add_joint(&mut self, joint: JointSpec) -> &mut JointSpec {
+        push(&mut self.joints, joint)
+    }
+
+    /// Adds a geom to the body and returns it for further editing
+    pub fn add_geom(&mut self, geom: GeomSpec) -> &mut GeomSpec {
+        push(&mut self.geoms, geom)
+    }
+
+    /// Adds a site to the body and returns it for further editing
+    pub fn add_site(&mut self, site: SiteSpec) -> &mut SiteSpec {
+        push(&mut self.sites, site)
+    }
+
+    /// Finds a body in the subtree rooted at this body by name
+    pub fn find_body_mut(&mut self, name: &str) -> Option<&mut BodySpec> {
+        if self.name.as_deref() == Some(name) {
+            return Some(self);
+        }
+        self.bodies
+            .iter_mut()
+            .find_map(|body| body.find_body_mut(name))
+    }
+
+    fn write_xml(&self, xml: &mut String, depth: usize) -> Result<(), Error> {
+        let indent = "  ".repeat(depth);
+        write!(xml, "{}<body", indent).unwrap();
+        write_name(xml, &self.name);
+        write_attr(xml, "pos", &vec3(&self.pos));
+        write_attr(xml, "quat", &quat(&self.quat));
+        if self.mocap {
+            write_attr(xml, "mocap", "true");
+        }
+        writeln!(xml, ">").unwrap();
+        self.write_children(xml, depth + 1)?;
+        writeln!(xml, "{}</body>", indent).unwrap();
+        Ok(())
+    }
+
+    fn write_children(&self, xml: &mut String, depth: usize) -> Result<(), Error> {
+        let indent = "  ".repeat(depth);
+        for joint in &self.joints {
+            write!(xml, "{}", indent).unwrap();
+            joint.write_xml(xml);
+        }
+        for geom in &self.geoms {
+            write!(xml, "{}", indent).unwrap();
+            geom.write_xml(xml)?;
+        }
+        for site in &self.sites {
+            write!(xml, "{}", indent).unwrap();
+            site.write_xml(xml);
+        }
+        for body in &self.bodies {
+            body.write_xml(xml, depth)?;
+        }
+        Ok(())
+    }
+}
+impl Default for BodySpec {
+    fn default() -> Self {
+        Self {
+            name: None,
+            pos: Vector3::zeros(),
+            quat: Quaternion::identity(),
+            mocap: false,
+            bodies: Vec::new(),
+            joints: Vec::new(),
+            geoms: Vec::new(),
+            sites: Vec::new(),
+        }
+    }
+}
+
+/// A joint, giving its body degrees of freedom relative to its parent
+#[derive(Debug, Clone)]
+pub struct JointSpec {
+    pub name: Option<String>,
+    pub joint_type: JointType,
+    /// Position of the joint in the frame of its body
+    pub pos: Vector3<f64>,
+    /// Axis of rotation for hinges, or translation for slides
+    pub axis: Vector3<f64>,
+    /// Limits of the joint. The joint is unlimited if this is `None`.
+    pub range: Option<[f64; 2]>,
+    pub stiffness: f64,
+    pub damping: f64,
+    pub armature: f64,
+}
+impl JointSpec {
+    /// Creates a joint of the given type with the given name
+    pub fn new(name: impl Into<String>, joint_type: JointType) -> Self {
+        Self {
+            name: Some(name.into()),
+            joint_type,
+            ..Default::default()
+        }
+    }
+
+    fn write_xml(&self, xml: &mut String) {
+        write!(xml, "<joint").unwrap();
+        write_name(xml, &self.name);
+        let joint_type = match self.joint_type {
+            JointType::FREE => "free",
+            JointType::BALL => "ball",
+            JointType::SLIDE => "slide",
+            JointType::HINGE => "hinge",
+        };
+        write_attr(xml, "type", joint_type);
+        write_attr(xml, "pos", &vec3(&self.pos));
+        write_attr(xml, "axis", &vec3(&self.axis));
+        if let Some([lower, upper]) = self.range {
+            write_attr(xml, "limited", "true");
+            write_attr(xml, "range", &format!("{} {}", lower, upper));
+        }
+        write_attr(xml, "stiffness", &self.stiffness.to_string());
+        write_attr(xml, "damping", &self.damping.to_string());
+        write_attr(xml, "armature", &self.armature.to_string());
+        writeln!(xml, "/>").unwrap();
+    }
+}
+impl Default for JointSpec {
+    fn default() -> Self {
+        Self {
+            name: None,
+            joint_type: JointType::HINGE,
+            pos: Vector3::zeros(),
+            axis: Vector3::z(),
+            range: None,
+            stiffness: 0.0,
+            damping: 0.0,
+            armature: 0.0,
+        }
+    }
+}
+
+/// A geom, giving its body a shape for collisions, inertia and rendering
+#[derive(Debug, Clone)]
+pub struct GeomSpec {
+    pub name: Option<String>,
+    pub geom_type: GeomType,
+    /// Type-specific size parameters. Unused entries are ignored.
+    pub size: Vector3<f64>,
+    /// Position in the frame of the body
+    pub pos: Vector3<f64>,
+    /// Orientation in the frame of the body
+    pub quat: Quaternion<f64>,
+    pub rgba: [f32; 4],
+    /// Mass of the geom. If `None`, it is computed from MuJoCo's default density.
+    pub mass: Option<f64>,
+    /// Sliding, torsional and rolling friction
+    pub friction: [f64; 3],
+    /// Name of the [`MeshSpec`] to use for [`GeomType::MESH`] geoms
+    pub mesh: Option<String>,
+    /// Name of the [`HFieldSpec`] to use for [`GeomType::HFIELD`] geoms
+    pub hfield: Option<String>,
+}
+impl GeomSpec {
+    /// Creates a geom of the given type and size
+    pub fn new(geom_type: GeomType, size: Vector3<f64>) -> Self {
+        Self {
+            geom_type,
+            size,
+            ..Default::default()
+        }
+    }
+
+    fn write_xml(&self, xml: &mut String) -> Result<(), Error> {
+        let geom_type = match self.geom_type {
+            GeomType::PLANE => "plane",
+            GeomType::HFIELD => "hfield",
+            GeomType::SPHERE => "sphere",
+            GeomType::CAPSULE => "capsule",
+            GeomType::ELLIPSOID => "ellipsoid",
+            GeomType::CYLINDER => "cylinder",
+            GeomType::BOX => "box",
+            GeomType::MESH => "mesh",
+            other => {
+                return Err(Error::InvalidSpec(format!(
+                    "{:?} geoms cannot be added to a model",
+                    other
+                )))
+            }
+        };
+        write!(xml, "<geom").unwrap();
+        write_name(xml, &self.name);
+        write_attr(xml, "type", geom_type);
+        write_attr(xml, "size", &vec3(&self.size));
+        write_attr(xml, "pos", &vec3(&self.pos));
+        write_attr(xml, "quat", &quat(&self.quat));
+        let [r, g, b, a] = self.rgba;
+        write_attr(xml, "rgba", &format!("{} {} {} {}", r, g, b, a));
+        if let Some(mass) = self.mass {
+            write_attr(xml, "mass", &mass.to_string());
+        }
+        let [slide, spin, roll] = self.friction;
+        write_attr(xml, "friction", &format!("{} {} {}", slide, spin, roll));
+        if let Some(mesh) = &self.mesh {
+            write_attr(xml, "mesh", &escape(mesh));
+        }
+        if let Some(hfield) = &self.hfield {
+            write_attr(xml, "hfield", &escape(hfield));
+        }
+        writeln!(xml, "/>").unwrap();
+        Ok(())
+    }
+}
+impl Default for GeomSpec {
+    fn default() -> Self {
+        Self {
+            name: None,
+            geom_type: GeomType::SPHERE,
+            size: Vector3::new(0.1, 0.0, 0.0),
+            pos: Vector3::zeros(),
+            quat: Quaternion::identity(),
+            rgba: [0.5, 0.5, 0.5, 1.0],
+            mass: None,
+            friction: [1.0, 0.005, 0.0001],
+            mesh: None,
+            hfield: None,
+        }
+    }
+}
+
+/// A site, marking a frame on its body for sensors, tendons and actuators
+#[derive(Debug, Clone)]
+pub struct SiteSpec {
+    pub name: Option<String>,
+    /// Position in the frame of the body
+    pub pos: Vector3<f64>,
+    /// Orientation in the frame of the body
+    pub quat: Quaternion<f64>,
+    /// Radius of the sphere used to render the site
+    pub size: f64,
+}
+impl SiteSpec {
+    /// Creates a site at the given position with the given name
+    pub fn new(name: impl Into<String>, pos: Vector3<f64>) -> Self {
+        Self {
+            name: Some(name.into()),
+            pos,
+            ..Default::default()
+        }
+    }
+
+    fn write_xml(&self, xml: &mut String) {
+        write!(xml, "<site").unwrap();
+        write_name(xml, &self.name);
+        write_attr(xml, "pos", &vec3(&self.pos));
+        write_attr(xml, "quat", &quat(&self.quat));
+        write_attr(xml, "size", &self.size.to_string());
+        writeln!(xml, "/>").unwrap();
+    }
+}
+impl Default for SiteSpec {
+    fn default() -> Self {
+        Self {
+            name: None,
+            pos: Vector3::zeros(),
+            quat: Quaternion::identity(),
+            size: 0.01,
+        }
+    }
+}
+
+/// What an actuator applies its force to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActuatorTarget {
+    Joint(String),
+    Site(String),
+}
+
+/// How an actuator turns its control input into a force
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ActuatorKind {
+    /// The control is the force, scaled by the gear
+    Motor,
+    /// The control is a target position, tracked with the given stiffness
+    Position { kp: f64 },
+    /// The control is a target velocity, tracked with the given gain
+    Velocity { kv: f64 },
+}
+
+/// An actuator, driving a joint or site from a control input
+#[derive(Debug, Clone)]
+pub struct ActuatorSpec {
+    pub name: Option<String>,
+    pub kind: ActuatorKind,
+    pub target: ActuatorTarget,
+    pub gear: f64,
+    /// Limits of the control input. The control is unlimited if this is `None`.
+    pub ctrlrange: Option<[f64; 2]>,
+}
+impl ActuatorSpec {
+    /// Creates an actuator of the given kind driving `target`
+    pub fn new(
+        name: impl Into<String>,
+        kind: ActuatorKind,
+        target: ActuatorTarget,
+    ) -> Self {
+        Self {
+            name: Some(name.into()),
+            kind,
+            target,
+            gear: 1.0,
+            ctrlrange: None,
+        }
+    }
+
+    fn write_xml(&self, xml: &mut String) {
+        let (element, gain) = match self.kind {
+            ActuatorKind::Motor => ("motor", None),
+            ActuatorKind::Position { kp } => ("position", Some(("kp", kp))),
+            ActuatorKind::Velocity { kv } => ("velocity", Some(("kv", kv))),
+        };
+        write!(xml, "    <{}", element).unwrap();
+        write_name(xml, &self.name);
+        match &self.target {
+            ActuatorTarget::Joint(joint) => write_attr(xml, "joint", &escape(joint)),
+            ActuatorTarget::Site(site) => write_attr(xml, "site", &escape(site)),
+        }
+        write_attr(xml, "gear", &self.gear.to_string());
+        if let Some((attr, value)) = gain {
+            write_attr(xml, attr, &value.to_string());
+        }
+        if let Some([lower, upper]) = self.ctrlrange {
+            write_attr(xml, "ctrllimited", "true");
+            write_attr(xml, "ctrlrange", &format!("{} {}", lower, upper));
+        }
+        writeln!(xml, "/>").unwrap();
+    }
+}
+
+/// A triangle mesh asset, used by [`GeomType::MESH`] geoms
+#[derive(Debug, Clone, Default)]
+pub struct MeshSpec {
+    pub name: String,
+    pub vertices: Vec<[f32; 3]>,
+    /// Triangles, as indices into `vertices`
+    pub faces: Vec<[u32; 3]>,
+}
+impl MeshSpec {
+    fn write_xml(&self, xml: &mut String) {
+        write!(xml, "    <mesh").unwrap();
+        write_attr(xml, "name", &escape(&self.name));
+        let vertices = self.vertices.iter().flatten().map(ToString::to_string);
+        write_attr(xml, "vertex", &itertools::join(vertices, " "));
+        if !self.faces.is_empty() {
+            let faces = self.faces.iter().flatten().map(ToString::to_string);
+            write_attr(xml, "face", &itertools::join(faces, " "));
+        }
+        writeln!(xml, "/>").unwrap();
+    }
+}
+
+/// A heightfield asset, used by [`GeomType::HFIELD`] geoms, such as terrain
+#[derive(Debug, Clone, Default)]
+pub struct HFieldSpec {
+    pub name: String,
+    pub nrow: usize,
+    pub ncol: usize,
+    /// Half-extents in x and y, the height of the highest elevation, and the
+    /// depth of the base below zero
+    pub size: [f64; 4],
+    /// `nrow * ncol` elevations in row-major order, scaled into `[0, 1]` by
+    /// MuJoCo
+    pub elevation: Vec<f32>,
+}
+impl HFieldSpec {
+    fn write_xml(&self, xml: &mut String) {
+        write!(xml, "    <hfield").unwrap();
+        write_attr(xml, "name", &escape(&self.name));
+        write_attr(xml, "nrow", &self.nrow.to_string());
+        write_attr(xml, "ncol", &self.ncol.to_string());
+        let [x, y, z, base] = self.size;
+        write_attr(xml, "size", &format!("{} {} {} {}", x, y, z, base));
+        let elevation = self.elevation.iter().map(ToString::to_string);
+        write_attr(xml, "elevation", &itertools::join(elevation, " "));
+        writeln!(xml, "/>").unwrap();
+    }
+}
+
+/// Pushes `item` onto `vec`, returning a reference to it
+fn push<T>(vec: &mut Vec<T>, item: T) -> &mut T {
+    vec.push(item);
+    vec.last_mut().unwrap()
+}
+
+fn write_attr(xml: &mut String, attr: &str, value: &str) {
+    write!(xml, " {}=\"{}\"", attr, value).unwrap();
+}
+
+fn write_name(xml: &mut String, name: &Option<String>) {
+    if let Some(name) = name {
+        write_attr(xml, "name", &escape(name));
+    }
+}
+
+fn vec3(v: &Vector3<f64>) -> String {
+    format!("{} {} {}", v.x, v.y, v.z)
+}
+
+fn quat(q: &Quaternion<f64>) -> String {
+    format!("{} {} {} {}", q.w, q.i, q.j, q.k)
+}
+
+/// Escapes the characters that are not allowed in XML attribute values
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ActuatorId, BodyId, JointId, SiteId};
+
+    fn cartpole() -> Spec {
+        let mut spec = Spec::new();
+        spec.model_name = Some("cart & pole".to_owned());
+        spec.worldbody.add_geom(GeomSpec {
+            name: Some("floor".to_owned()),
+            ..GeomSpec::new(GeomType::PLANE, Vector3::new(5.0, 5.0, 0.1))
+        });
+
+        let cart = spec.worldbody.add_body(BodySpec {
+            pos: Vector3::new(0.0, 0.0, 0.5),
+            ..BodySpec::new("cart")
+        });
+        cart.add_joint(JointSpec {
+            axis: Vector3::x(),
+            range: Some([-1.0, 1.0]),
+            ..JointSpec::new("slider", JointType::SLIDE)
+        });
+        cart.add_geom(GeomSpec::new(GeomType::BOX, Vector3::new(0.2, 0.1, 0.05)));
+
+        let pole = cart.add_body(BodySpec::new("pole"));
+        pole.add_joint(JointSpec {
+            axis: Vector3::y(),
+            damping: 0.1,
+            ..JointSpec::new("hinge", JointType::HINGE)
+        });
+        pole.add_geom(GeomSpec {
+            pos: Vector3::new(0.0, 0.0, 0.25),
+            ..GeomSpec::new(GeomType::CAPSULE, Vector3::new(0.02, 0.25, 0.0))
+        });
+        pole.add_site(SiteSpec::new("tip", Vector3::new(0.0, 0.0, 0.5)));
+
+        spec.add_actuator(ActuatorSpec {
+            ctrlrange: Some([-1.0, 1.0]),
+            ..ActuatorSpec::new(
+                "push",
+                ActuatorKind::Motor,
+                ActuatorTarget::Joint("slider".to_owned()),
+            )
+        });
+        spec
+    }
+
+    #[test]
+    fn compile() {
+        let m = cartpole().compile().unwrap();
+        assert_eq!(m.nbody(), 3);
+        assert_eq!(m.nq(), 2);
+        assert_eq!(m.nu(), 1);
+        assert_eq!(m.name_to_id("pole"), Some(BodyId::new(2)));
+        assert_eq!(m.name_to_id("hinge"), Some(JointId::new(1)));
+        assert_eq!(m.name_to_id("tip"), Some(SiteId::new(0)));
+        assert_eq!(m.name_to_id("push"), Some(ActuatorId::new(0)));
+        assert_eq!(unsafe { *(*m.ptr()).jnt_range.add(1) }, 1.0);
+    }
+
+    #[test]
+    fn edit_and_recompile() {
+        let mut spec = cartpole();
+        let pole = spec.worldbody.find_body_mut("pole").unwrap();
+        pole.add_body(BodySpec::new("pole2"))
+            .add_joint(JointSpec::new("hinge2", JointType::HINGE));
+        pole.bodies[0].add_geom(GeomSpec::default());
+
+        let m = spec.compile().unwrap();
+        assert_eq!(m.nbody(), 4);
+        assert_eq!(m.nq(), 3);
+    }
+
+    #[test]
+    fn recompile() {
+        let mut spec = cartpole();
+        let mut sim = crate::Simulation::new(spec.compile().unwrap());
+        sim.qpos_mut().copy_from_slice(&[0.3, 0.2]);
+        sim.qvel_mut().copy_from_slice(&[-0.5, 1.5]);
+        sim.ctrl_mut()[0] = 0.7;
+
+        // New objects come first, so that the old ones move
+        let cart = spec.worldbody.find_body_mut("cart").unwrap();
+        cart.joints
+            .insert(0, JointSpec::new("lift", JointType::SLIDE));
+        spec.actuators.insert(
+            0,
+            ActuatorSpec::new(
+                "raise",
+                ActuatorKind::Motor,
+                ActuatorTarget::Joint("lift".to_owned()),
+            ),
+        );
+
+        let (m, state) = spec.recompile(sim.state()).unwrap();
+        assert!(Arc::ptr_eq(&m, state.model()));
+        assert_eq!(m.name_to_id("slider"), Some(JointId::new(1)));
+        assert_eq!(state.qpos(), &[0.0, 0.3, 0.2]);
+        assert_eq!(state.qvel(), &[0.0, -0.5, 1.5]);
+        assert_eq!(state.ctrl(), &[0.0, 0.7]);
+
+        // A joint that changes type starts over
+        let pole = spec.worldbody.find_body_mut("pole").unwrap();
+        pole.joints[0].joint_type = JointType::BALL;
+        let (_, state) = spec.recompile(&state).unwrap();
+        assert_eq!(state.qpos(), &[0.0, 0.3, 1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(state.qvel(), &[0.0, -0.5, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn assets() {
+        let mut spec = Spec::new();
+        spec.add_mesh(MeshSpec {
+            name: "tetra".to_owned(),
+            vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [0., 0., 1.]],
+            faces: vec![[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]],
+        });
+        spec.add_hfield(HFieldSpec {
+            name: "terrain".to_owned(),
+            nrow: 2,
+            ncol: 3,
+            size: [1.0, 1.0, 0.2, 0.1],
+            elevation: vec![0.0, 0.5, 1.0, 1.0, 0.5, 0.0],
+        });
+        spec.worldbody.add_geom(GeomSpec {
+            hfield: Some("terrain".to_owned()),
+            ..GeomSpec::new(GeomType::HFIELD, Vector3::zeros())
+        });
+        spec.worldbody
+            .add_body(BodySpec::new("rock"))
+            .add_geom(GeomSpec {
+                mesh: Some("tetra".to_owned()),
+                ..GeomSpec::new(GeomType::MESH, Vector3::zeros())
+            });
+
+        let m = spec.compile().unwrap();
+        assert_eq!(m.nmesh(), 1);
+        assert_eq!(m.ngeom(), 2);
+    }
+
+    #[test]
+    fn invalid() {
+        let mut spec = Spec::new();
+        spec.worldbody.add_body(BodySpec::new("body0"));
+        spec.worldbody.add_body(BodySpec::new("body0"));
+        assert!(matches!(spec.compile(), Err(Error::Xml { .. })));
+
+        let mut spec = Spec::new();
+        spec.worldbody
+            .add_body(BodySpec::new("body0"))
+            .add_geom(GeomSpec::new(GeomType::ARROW, Vector3::zeros()));
+        assert_eq!(
+            spec.to_xml(),
+            Err(Error::InvalidSpec(
+                "ARROW geoms cannot be added to a model".to_owned()
+            ))
+        );
+        assert!(matches!(spec.compile(), Err(Error::InvalidSpec(_))));
+    }
+}
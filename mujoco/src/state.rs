@@ -1,24 +1,43 @@
+use std::sync::Arc;
+
 use mujoco_rs_sys::mjData;
+use nalgebra::{MatrixView3xX, MatrixView4xX, MatrixView6xX, MatrixViewMut6xX};
+
+use crate::helpers::{borrow_slice, borrow_slice_mut};
+use crate::snapshot::{StateMask, StateSnapshot};
+use crate::{Error, Model};
 
 /// The time-dependent state of a MuJoCo simulation. Analagous to mjData from
 /// the C API
+///
+/// A `State` keeps the [`Model`] that it was created from alive, and is only
+/// ever used with that model. It can be sent to another thread, but not shared
+/// between threads, since even reading from it can make MuJoCo write to it.
 #[derive(Debug)]
 pub struct State {
     pub(crate) ptr: *mut mujoco_rs_sys::no_render::mjData,
+    pub(crate) model: Arc<Model>,
 }
 
+// The `mjData` is owned by the `State` and only touched through it
 unsafe impl Send for State {}
-unsafe impl Sync for State {}
 
 impl State {
-    /// Creates a new `State` from a [`Model`]
-    pub fn new(model: &crate::Model) -> Self {
+    /// Creates a new `State` for a [`Model`]. Pass an `Arc<Model>` to share the
+    /// model between several `State`s.
+    pub fn new(model: impl Into<Arc<Model>>) -> Self {
+        let model = model.into();
         let ptr = unsafe { mujoco_rs_sys::no_render::mj_makeData(model.ptr) };
         assert_ne!(ptr, std::ptr::null_mut());
         // Do one forward step to initialize all fields
         // TODO: Double check this is necessary
         unsafe { mujoco_rs_sys::no_render::mj_forward(model.ptr, ptr) };
-        Self { ptr }
+        Self { ptr, model }
+    }
+
+    /// The [`Model`] that the `State` was created from
+    pub fn model(&self) -> &Arc<Model> {
+        &self.model
     }
 
     /// Gets the low level [`mjData`] that the `Data` uses under the hood
@@ -33,6 +52,240 @@ impl State {
             (*mj_data).time
         }
     }
+
+    /// Copies the components of the state in `mask` into an owned
+    /// [`StateSnapshot`], to be restored later with [`State::restore()`]
+    ///
+    /// ```ignore
+    /// let snapshot = state.snapshot(StateMask::INTEGRATION);
+    /// ```
+    pub fn snapshot(&self, mask: StateMask) -> StateSnapshot {
+        unsafe { StateSnapshot::new(&*self.model.ptr, self.ptr, mask) }
+    }
+
+    /// Overwrites the components of the state held by `snapshot`, leaving the
+    /// rest untouched. Quantities derived from the state, such as body poses,
+    /// are only updated by the next step or call to `mj_forward`.
+    ///
+    /// Fails if `snapshot` was taken with a model of a different size.
+    pub fn restore(&mut self, snapshot: &StateSnapshot) -> Result<(), Error> {
+        let m = unsafe { &*self.model.ptr };
+        let expected = snapshot.mask().size(m);
+        let actual = snapshot.as_slice().len();
+        if expected != actual {
+            return Err(Error::SnapshotSize { expected, actual });
+        }
+        unsafe { snapshot.write(m, self.ptr) };
+        Ok(())
+    }
+}
+
+// Views of the arrays in the `mjData`
+impl State {
+    /// Returns the positions of the bodies in the world frame, one column per
+    /// body
+    pub fn xpos(&self) -> MatrixView3xX<'_, f64> {
+        let xpos = unsafe { (*self.ptr).xpos };
+        let nbody = self.model.nbody();
+        MatrixView3xX::from_slice(self.data_slice(xpos, 3 * nbody), nbody)
+    }
+
+    /// Returns the orientations of the bodies in the world frame as `w, x, y,
+    /// z` quaternions, one column per body
+    pub fn xquat(&self) -> MatrixView4xX<'_, f64> {
+        let xquat = unsafe { (*self.ptr).xquat };
+        let nbody = self.model.nbody();
+        MatrixView4xX::from_slice(self.data_slice(xquat, 4 * nbody), nbody)
+    }
+
+    /// Returns generalized positions
+    pub fn qpos(&self) -> &[f64] {
+        let qpos = unsafe { (*self.ptr).qpos };
+        self.data_slice(qpos, self.model.nq())
+    }
+
+    /// Mutable counterpart of [`State::qpos()`]
+    pub fn qpos_mut(&mut self) -> &mut [f64] {
+        let qpos = unsafe { (*self.ptr).qpos };
+        let nq = self.model.nq();
+        self.data_slice_mut(qpos, nq)
+    }
+
+    /// Returns generalized velocities
+    pub fn qvel(&self) -> &[f64] {
+        let qvel = unsafe { (*self.ptr).qvel };
+        self.data_slice(qvel, self.model.nv())
+    }
+
+    /// Mutable counterpart of [`State::qvel()`]
+    pub fn qvel_mut(&mut self) -> &mut [f64] {
+        let qvel = unsafe { (*self.ptr).qvel };
+        let nv = self.model.nv();
+        self.data_slice_mut(qvel, nv)
+    }
+
+    /// Returns generalized accelerations
+    pub fn qacc(&self) -> &[f64] {
+        let qacc = unsafe { (*self.ptr).qacc };
+        self.data_slice(qacc, self.model.nv())
+    }
+
+    /// Mutable counterpart of [`State::qacc()`]
+    pub fn qacc_mut(&mut self) -> &mut [f64] {
+        let qacc = unsafe { (*self.ptr).qacc };
+        let nv = self.model.nv();
+        self.data_slice_mut(qacc, nv)
+    }
+
+    /// Returns the generalized forces computed by inverse dynamics
+    pub fn qfrc_inverse(&self) -> &[f64] {
+        let qfrc_inverse = unsafe { (*self.ptr).qfrc_inverse };
+        self.data_slice(qfrc_inverse, self.model.nv())
+    }
+
+    /// Returns actuator activations
+    pub fn act(&self) -> &[f64] {
+        let act = unsafe { (*self.ptr).act };
+        self.data_slice(act, self.model.na())
+    }
+
+    /// Mutable counterpart of [`State::act()`]
+    pub fn act_mut(&mut self) -> &mut [f64] {
+        let act = unsafe { (*self.ptr).act };
+        let na = self.model.na();
+        self.data_slice_mut(act, na)
+    }
+
+    /// Returns the control vector
+    pub fn ctrl(&self) -> &[f64] {
+        let ctrl = unsafe { (*self.ptr).ctrl };
+        self.data_slice(ctrl, self.model.nu())
+    }
+
+    /// Mutable counterpart of [`State::ctrl()`]
+    pub fn ctrl_mut(&mut self) -> &mut [f64] {
+        let ctrl = unsafe { (*self.ptr).ctrl };
+        let nu = self.model.nu();
+        self.data_slice_mut(ctrl, nu)
+    }
+
+    /// Returns the force that each actuator produces in its transmission
+    pub fn actuator_forces(&self) -> &[f64] {
+        let actuator_force = unsafe { (*self.ptr).actuator_force };
+        self.data_slice(actuator_force, self.model.nu())
+    }
+
+    /// Returns the length of each actuator's transmission
+    pub fn actuator_lengths(&self) -> &[f64] {
+        let actuator_length = unsafe { (*self.ptr).actuator_length };
+        self.data_slice(actuator_length, self.model.nu())
+    }
+
+    /// Returns the velocity of each actuator's transmission
+    pub fn actuator_velocities(&self) -> &[f64] {
+        let actuator_velocity = unsafe { (*self.ptr).actuator_velocity };
+        self.data_slice(actuator_velocity, self.model.nu())
+    }
+
+    /// Returns the wrenches applied to the bodies in the world frame, as
+    /// `force, torque` at each body's center of mass, one column per body
+    pub fn xfrc_applied(&self) -> MatrixView6xX<'_, f64> {
+        let xfrc_applied = unsafe { (*self.ptr).xfrc_applied };
+        let nbody = self.model.nbody();
+        MatrixView6xX::from_slice(self.data_slice(xfrc_applied, 6 * nbody), nbody)
+    }
+
+    /// Mutable counterpart of [`State::xfrc_applied()`]
+    pub fn xfrc_applied_mut(&mut self) -> MatrixViewMut6xX<'_, f64> {
+        let xfrc_applied = unsafe { (*self.ptr).xfrc_applied };
+        let nbody = self.model.nbody();
+        MatrixViewMut6xX::from_slice(
+            self.data_slice_mut(xfrc_applied, 6 * nbody),
+            nbody,
+        )
+    }
+
+    /// Returns the generalized forces applied to the degrees of freedom
+    pub fn qfrc_applied(&self) -> &[f64] {
+        let qfrc_applied = unsafe { (*self.ptr).qfrc_applied };
+        self.data_slice(qfrc_applied, self.model.nv())
+    }
+
+    /// Mutable counterpart of [`State::qfrc_applied()`]
+    pub fn qfrc_applied_mut(&mut self) -> &mut [f64] {
+        let qfrc_applied = unsafe { (*self.ptr).qfrc_applied };
+        let nv = self.model.nv();
+        self.data_slice_mut(qfrc_applied, nv)
+    }
+
+    /// Returns the bias forces: the Coriolis, centrifugal and gravitational
+    /// forces that are subtracted to find the accelerations
+    pub fn qfrc_bias(&self) -> &[f64] {
+        let qfrc_bias = unsafe { (*self.ptr).qfrc_bias };
+        self.data_slice(qfrc_bias, self.model.nv())
+    }
+
+    /// Returns the passive forces from springs, dampers and fluid drag
+    pub fn qfrc_passive(&self) -> &[f64] {
+        let qfrc_passive = unsafe { (*self.ptr).qfrc_passive };
+        self.data_slice(qfrc_passive, self.model.nv())
+    }
+
+    /// Returns the forces produced by the actuators, as generalized forces
+    pub fn qfrc_actuator(&self) -> &[f64] {
+        let qfrc_actuator = unsafe { (*self.ptr).qfrc_actuator };
+        self.data_slice(qfrc_actuator, self.model.nv())
+    }
+
+    /// Returns the forces from contacts, limits and other constraints, as
+    /// generalized forces
+    pub fn qfrc_constraint(&self) -> &[f64] {
+        let qfrc_constraint = unsafe { (*self.ptr).qfrc_constraint };
+        self.data_slice(qfrc_constraint, self.model.nv())
+    }
+
+    /// Returns the external contact forces on the bodies as `torque, force`
+    /// wrenches in their center of mass frames, one column per body
+    pub fn cfrc_ext(&self) -> MatrixView6xX<'_, f64> {
+        let cfrc_ext = unsafe { (*self.ptr).cfrc_ext };
+        let nbody = self.model.nbody();
+        MatrixView6xX::from_slice(self.data_slice(cfrc_ext, 6 * nbody), nbody)
+    }
+
+    /// Returns the sensor data, laid out as in the model's sensors
+    pub fn sensordata(&self) -> &[f64] {
+        let sensordata = unsafe { (*self.ptr).sensordata };
+        self.data_slice(sensordata, self.model.nsensordata())
+    }
+
+    /// Borrows an array of the `mjData` for as long as `self` is borrowed
+    fn data_slice(&self, ptr: *const f64, len: usize) -> &[f64] {
+        unsafe { borrow_slice(ptr, len) }
+    }
+
+    /// Mutably borrows an array of the `mjData` for as long as `self` is
+    /// mutably borrowed
+    fn data_slice_mut(&mut self, ptr: *mut f64, len: usize) -> &mut [f64] {
+        unsafe { borrow_slice_mut(ptr, len) }
+    }
+}
+
+impl Clone for State {
+    /// Copies the state into a new `State` for the same [`Model`]
+    fn clone(&self) -> Self {
+        let ptr = unsafe {
+            mujoco_rs_sys::no_render::mj_copyData(
+                std::ptr::null_mut(),
+                self.model.ptr,
+                self.ptr,
+            )
+        };
+        assert_ne!(ptr, std::ptr::null_mut());
+        Self {
+            ptr,
+            model: self.model.clone(),
+        }
+    }
 }
 
 impl Drop for State {
@@ -44,14 +297,126 @@ impl Drop for State {
 #[cfg(test)]
 mod tests {
     use crate::tests::SIMPLE_XML;
-    use crate::Model;
 
     use super::*;
 
     #[test]
     fn new() {
         let model = Model::from_xml_str(*SIMPLE_XML).unwrap();
-        let state = State::new(&model);
+        let state = State::new(model);
         assert_ne!(state.ptr, std::ptr::null_mut());
     }
+
+    #[test]
+    fn shared_model() {
+        let model = Arc::new(Model::from_xml_str(*SIMPLE_XML).unwrap());
+        let a = State::new(model.clone());
+        let b = State::new(model.clone());
+        assert!(Arc::ptr_eq(a.model(), b.model()));
+
+        // Each `State` keeps the model alive
+        drop(model);
+        let handle = std::thread::spawn(move || a.time());
+        assert_eq!(handle.join().unwrap(), 0.0);
+        assert_eq!(b.model().nbody(), 2);
+    }
+
+    #[test]
+    fn clone_is_independent() {
+        let mut sim = crate::Simulation::new(Model::from_xml_str(*SIMPLE_XML).unwrap());
+        for _ in 0..10 {
+            sim.step();
+        }
+        let original = sim.state().clone();
+        assert_ne!(original.ptr, sim.state().ptr);
+        assert!(Arc::ptr_eq(original.model(), sim.model()));
+        assert_eq!(original.time(), sim.state().time());
+        let qpos = sim.qpos().to_vec();
+
+        // Stepping the clone leaves the original as it was, and vice versa
+        let mut copy = crate::Simulation::from_state(original.clone());
+        for _ in 0..10 {
+            copy.step();
+        }
+        assert_ne!(copy.qpos(), qpos.as_slice());
+        let original = crate::Simulation::from_state(original);
+        assert_eq!(original.qpos(), qpos.as_slice());
+        assert_eq!(original.state().time(), sim.state().time());
+        assert_eq!(sim.qpos(), qpos.as_slice());
+    }
+
+    #[test]
+    fn views() {
+        let mut state = State::new(Model::from_xml_str(*SIMPLE_XML).unwrap());
+        let model = state.model().clone();
+        assert_eq!(state.qpos().len(), model.nq());
+        assert_eq!(state.qvel().len(), model.nv());
+        assert_eq!(state.ctrl().len(), model.nu());
+        assert_eq!(state.xpos().ncols(), model.nbody());
+        assert_eq!(state.cfrc_ext().ncols(), model.nbody());
+        assert_eq!(state.sensordata().len(), model.nsensordata());
+
+        // The views borrow the same arrays that a `Simulation` uses
+        state.qpos_mut()[0] = 0.25;
+        state.qvel_mut()[0] = -1.0;
+        state.xfrc_applied_mut()[(2, 1)] = 3.0;
+        let sim = crate::Simulation::from_state(state);
+        assert_eq!(sim.qpos()[0], 0.25);
+        assert_eq!(sim.qvel()[0], -1.0);
+        assert_eq!(sim.xfrc_applied()[(2, 1)], 3.0);
+        assert_eq!(sim.qpos(), sim.state().qpos());
+    }
+
+    #[test]
+    fn snapshot_and_restore() {
+        let model = Arc::new(Model::from_xml_str(*SIMPLE_XML).unwrap());
+        let mut sim = crate::Simulation::new(model.clone());
+        for _ in 0..10 {
+            sim.step();
+        }
+        let snapshot = sim.state().snapshot(StateMask::INTEGRATION);
+        assert_eq!(snapshot.time(), Some(sim.state().time()));
+        assert_eq!(
+            snapshot.component(&model, StateMask::QPOS),
+            Some(sim.qpos())
+        );
+        assert_eq!(
+            snapshot.component(&model, StateMask::CTRL),
+            Some(sim.ctrl())
+        );
+
+        // Replaying from the snapshot is deterministic
+        for _ in 0..10 {
+            sim.step();
+        }
+        let qpos = sim.qpos().to_vec();
+        sim.state_mut().restore(&snapshot).unwrap();
+        assert_eq!(sim.state().snapshot(StateMask::INTEGRATION), snapshot);
+        for _ in 0..10 {
+            sim.step();
+        }
+        assert_eq!(sim.qpos(), qpos.as_slice());
+
+        // Components outside the mask are left alone
+        let time = sim.state().time();
+        let qvel = sim.state().snapshot(StateMask::QVEL);
+        sim.state_mut().restore(&qvel).unwrap();
+        assert_eq!(sim.state().time(), time);
+    }
+
+    #[test]
+    fn restore_size_mismatch() {
+        let mut state = State::new(Model::from_xml_str(*SIMPLE_XML).unwrap());
+        let snapshot = StateSnapshot::from_raw(StateMask::QPOS, vec![0.0]);
+        assert_eq!(
+            state.restore(&snapshot),
+            Err(Error::SnapshotSize {
+                expected: state.model().nq(),
+                actual: 1
+            })
+        );
+        let empty = state.snapshot(StateMask::EMPTY);
+        assert!(empty.as_slice().is_empty());
+        assert!(state.restore(&empty).is_ok());
+    }
 }
@@ -2,12 +2,15 @@ pub use mujoco_rs_sys::no_render::mjMAXVFS as MAX_FILES;
 pub use mujoco_rs_sys::no_render::mjMAXVFSNAME as MAX_FILENAME_LEN;
 
 use std::ffi::{CStr, CString};
+use std::path::{Path, PathBuf};
 
 /// An error when adding a file to a [`Vfs`] via [`Vfs::add_file()`]
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub enum AddError {
     VfsFull,
     RepeatedName,
+    /// The filename contains a null byte or is longer than [`MAX_FILENAME_LEN`]
+    InvalidName,
 }
 impl std::fmt::Display for AddError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -16,9 +19,51 @@ impl std::fmt::Display for AddError {
 }
 impl std::error::Error for AddError {}
 
+/// An error when adding files from disk to a [`Vfs`] via [`Vfs::add_path()`],
+/// [`Vfs::add_paths()`] or [`Vfs::add_dir()`]
+#[derive(Debug)]
+pub enum MountError {
+    /// The file or directory at the path could not be read
+    Io(PathBuf, std::io::Error),
+    /// The filename could not be represented as UTF-8
+    NonUtf8Path(PathBuf),
+    /// The file was read, but could not be added to the `Vfs`
+    Add(PathBuf, AddError),
+}
+impl std::fmt::Display for MountError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            Self::NonUtf8Path(path) => {
+                write!(f, "path is not valid UTF-8: {}", path.display())
+            }
+            Self::Add(path, e) => write!(f, "{}: {}", path.display(), e),
+        }
+    }
+}
+impl std::error::Error for MountError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(_, e) => Some(e),
+            Self::NonUtf8Path(_) => None,
+            Self::Add(_, e) => Some(e),
+        }
+    }
+}
+
+/// A virtual file system, holding files in memory that MuJoCo reads in place of
+/// files on disk when compiling a [`Model`](crate::Model).
+///
+/// MuJoCo matches files by filename alone, so any directories in the names
+/// given to a `Vfs` are ignored, and two files with the same filename cannot be
+/// added even if they come from different directories.
 pub struct Vfs {
     pub(crate) vfs: Box<mujoco_rs_sys::no_render::mjVFS_>,
 }
+
+// The `Vfs` owns all of the file contents that it points to
+unsafe impl Send for Vfs {}
+
 impl Vfs {
     /// Initializes a new empty `Vfs`
     pub fn new() -> Self {
@@ -38,10 +83,18 @@ impl Vfs {
         }
     }
 
+    /// Checks whether a file with the same filename as `path` is in the `Vfs`
+    pub fn contains_file(&self, path: impl AsRef<Path>) -> bool {
+        path.as_ref()
+            .to_str()
+            .and_then(|name| CString::new(name).ok())
+            .and_then(|name| self.get_idx(&name))
+            .is_some()
+    }
+
     /// Gets a file's contents from the `Vfs`
-    #[allow(dead_code)]
     pub fn get_file(&self, filename: &str) -> Option<&[u8]> {
-        let idx = self.get_idx(&CString::new(filename).unwrap())?;
+        let idx = self.get_idx(&CString::new(filename).ok()?)?;
         let file_size = self.vfs.filesize[idx] as usize;
         let start_ptr = self.vfs.filedata[idx] as *const u8;
         Some(unsafe { std::slice::from_raw_parts(start_ptr, file_size) })
@@ -50,7 +103,10 @@ impl Vfs {
     /// Deletes a file from the `Vfs` if it exists, and returns if such a file
     /// was found
     pub fn delete_file(&mut self, filename: &str) -> bool {
-        let c_str = CString::new(filename).unwrap();
+        let c_str = match CString::new(filename) {
+            Ok(c_str) => c_str,
+            Err(_) => return false,
+        };
         let result = unsafe {
             mujoco_rs_sys::no_render::mj_deleteFileVFS(&mut *self.vfs, c_str.as_ptr())
         };
@@ -64,7 +120,11 @@ impl Vfs {
         filename: &str,
         contents: &[u8],
     ) -> Result<(), AddError> {
-        let filename = CString::new(filename).unwrap();
+        // MuJoCo aborts on names that are too long, so we check them up front
+        if filename.len() >= MAX_FILENAME_LEN as usize {
+            return Err(AddError::InvalidName);
+        }
+        let filename = CString::new(filename).map_err(|_| AddError::InvalidName)?;
         let file_size = contents.len();
         let add_errno = unsafe {
             mujoco_rs_sys::no_render::mj_makeEmptyFileVFS(
@@ -90,6 +150,53 @@ impl Vfs {
         file_slice.copy_from_slice(contents);
         Ok(())
     }
+
+    /// Reads a file from disk and adds it to the `Vfs` under its filename
+    pub fn add_path(&mut self, path: impl AsRef<Path>) -> Result<(), MountError> {
+        let path = path.as_ref();
+        let filename = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| MountError::NonUtf8Path(path.to_owned()))?;
+        let contents =
+            std::fs::read(path).map_err(|e| MountError::Io(path.to_owned(), e))?;
+        self.add_file(filename, &contents)
+            .map_err(|e| MountError::Add(path.to_owned(), e))
+    }
+
+    /// Reads each file in `paths` from disk and adds it to the `Vfs`. Files
+    /// added before an error is encountered are kept.
+    pub fn add_paths<P: AsRef<Path>>(
+        &mut self,
+        paths: impl IntoIterator<Item = P>,
+    ) -> Result<(), MountError> {
+        paths.into_iter().try_for_each(|path| self.add_path(path))
+    }
+
+    /// Recursively adds every file under `dir` to the `Vfs`. Symbolic links to
+    /// files are followed, but symbolic links to directories are skipped, as
+    /// they could lead back up the tree. Files added before an error is
+    /// encountered are kept.
+    pub fn add_dir(&mut self, dir: impl AsRef<Path>) -> Result<(), MountError> {
+        let dir = dir.as_ref();
+        let entries =
+            std::fs::read_dir(dir).map_err(|e| MountError::Io(dir.to_owned(), e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| MountError::Io(dir.to_owned(), e))?;
+            let path = entry.path();
+            let file_type = entry
+                .file_type()
+                .map_err(|e| MountError::Io(path.clone(), e))?;
+            if file_type.is_dir() {
+                self.add_dir(&path)?;
+            } else if file_type.is_symlink() && path.is_dir() {
+                continue;
+            } else {
+                self.add_path(&path)?;
+            }
+        }
+        Ok(())
+    }
 }
 impl Default for Vfs {
     fn default() -> Self {
@@ -112,7 +219,8 @@ impl Drop for Vfs {
 
 #[cfg(test)]
 mod tests {
-    use super::{AddError, Vfs};
+    use super::{AddError, MountError, Vfs};
+    use crate::tests::{PKG_ROOT, SIMPLE_XML_PATH};
 
     #[test]
     fn new() {
@@ -150,4 +258,53 @@ mod tests {
         let second_add_result = vfs.add_file(filename, "asdf".as_bytes());
         assert!(second_add_result == Err(AddError::RepeatedName));
     }
+
+    #[test]
+    fn add_invalid_name() {
+        let mut vfs = Vfs::new();
+        let result = vfs.add_file("as\0df", "asdf".as_bytes());
+        assert_eq!(result, Err(AddError::InvalidName));
+        let long_name = "a".repeat(super::MAX_FILENAME_LEN as usize);
+        let result = vfs.add_file(&long_name, "asdf".as_bytes());
+        assert_eq!(result, Err(AddError::InvalidName));
+        assert_eq!(vfs.get_file("as\0df"), None);
+    }
+
+    #[test]
+    fn add_path() {
+        let mut vfs = Vfs::new();
+        vfs.add_path(&*SIMPLE_XML_PATH).unwrap();
+        let contents = std::fs::read(&*SIMPLE_XML_PATH).unwrap();
+        assert_eq!(vfs.get_file("simple.xml").unwrap(), contents.as_slice());
+    }
+
+    #[test]
+    fn add_missing_path() {
+        let mut vfs = Vfs::new();
+        let result = vfs.add_paths(&[PKG_ROOT.join("tests").join("missing.xml")]);
+        assert!(matches!(result, Err(MountError::Io(..))));
+    }
+
+    #[test]
+    fn add_dir() {
+        let mut vfs = Vfs::new();
+        vfs.add_dir(PKG_ROOT.join("tests")).unwrap();
+        assert!(vfs.get_file("simple.xml").is_some());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn add_dir_with_symlink_loop() {
+        let dir = std::env::temp_dir()
+            .join(format!("mujoco-rust-vfs-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("sub").join("a.xml"), "<mujoco/>").unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("sub").join("parent")).unwrap();
+
+        let mut vfs = Vfs::new();
+        let result = vfs.add_dir(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        result.unwrap();
+        assert_eq!(vfs.get_file("a.xml").unwrap(), b"<mujoco/>");
+    }
 }
@@ -0,0 +1,62 @@
+//! MuJoCo can only save the model it most recently compiled from XML, so this
+//! lives in its own test binary where no other test can load a model between
+//! loading and saving.
+
+use mujoco_rust::{Error, GeomId, Keyframe, Model};
+
+const SIMPLE_XML: &str = include_str!("res/simple.xml");
+
+#[test]
+fn save_xml_roundtrip() {
+    let m = Model::from_xml_str(SIMPLE_XML).unwrap();
+    let geom1: GeomId = m.name_to_id("geom1").unwrap();
+
+    // Changes to real-valued fields should survive the roundtrip
+    unsafe { *(*m.ptr()).geom_size.add(geom1.index() * 3) = 0.5 };
+    let xml = m.to_xml_string().unwrap();
+
+    let reloaded = Model::from_xml_str(&xml).unwrap();
+    assert_eq!(reloaded.nbody(), m.nbody());
+    assert_eq!(reloaded.ngeom(), m.ngeom());
+    assert_eq!(reloaded.name_to_id("geom1"), Some(geom1));
+    assert_eq!(unsafe { *(*reloaded.ptr()).geom_size.add(geom1.index() * 3) }, 0.5);
+
+    // Models that were not compiled from XML themselves can't be saved
+    assert!(matches!(
+        reloaded.clone().to_xml_string(),
+        Err(Error::SaveXml(_))
+    ));
+    let from_bytes = Model::from_bytes(reloaded.to_vec()).unwrap();
+    assert!(matches!(from_bytes.to_xml_string(), Err(Error::SaveXml(_))));
+    assert!(reloaded.to_xml_string().is_ok());
+
+    let keyframe = Keyframe {
+        name: "start".to_owned(),
+        time: 0.5,
+        qpos: Vec::new(),
+        qvel: Vec::new(),
+        act: Vec::new(),
+        ctrl: Vec::new(),
+        mocap_pos: Vec::new(),
+        mocap_quat: Vec::new(),
+    };
+    let with_keyframe = reloaded
+        .to_xml_string_with_keyframes(std::slice::from_ref(&keyframe))
+        .unwrap();
+
+    // Once another model has been compiled, the original can no longer be saved
+    assert!(matches!(m.to_xml_string(), Err(Error::SaveXml(_))));
+
+    // Nor after a failed attempt to compile one
+    assert!(Model::from_xml_str("<mujoco>").is_err());
+    assert!(matches!(reloaded.to_xml_string(), Err(Error::SaveXml(_))));
+    assert!(matches!(
+        reloaded.to_xml_string_with_keyframes(&[]),
+        Err(Error::SaveXml(_))
+    ));
+
+    let keyframes = Model::from_xml_str(&with_keyframe).unwrap().keyframes();
+    assert_eq!(keyframes.len(), 1);
+    assert_eq!(keyframes[0].name, "start");
+    assert_eq!(keyframes[0].time, 0.5);
+}